@@ -0,0 +1,286 @@
+use criterion::measurement::{Measurement, ValueFormatter};
+use criterion::{
+    criterion_group, criterion_main, BatchSize, BenchmarkGroup, Criterion, Throughput,
+};
+use hyper::body::Bytes;
+use hyper::Body;
+use kaon::{ApiConfig, Context, Kaon, Payload};
+use mockito::{Matcher, Mock, ServerGuard};
+use serde::Deserialize;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use tokio::runtime::{Builder, Runtime};
+
+const REQUEST_ID: &str = "8476a536-e9f4-11e8-9739-2dfe598c3fcd";
+
+// allocations are counted per thread so the mock runtime api serving requests on its own
+// thread does not show up in the numbers
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|allocations| allocations.set(allocations.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+struct Allocations;
+
+impl Measurement for Allocations {
+    type Intermediate = u64;
+    type Value = u64;
+
+    fn start(&self) -> u64 {
+        ALLOCATIONS.with(Cell::get)
+    }
+
+    fn end(&self, started: u64) -> u64 {
+        ALLOCATIONS.with(Cell::get) - started
+    }
+
+    fn add(&self, first: &u64, second: &u64) -> u64 {
+        first + second
+    }
+
+    fn zero(&self) -> u64 {
+        0
+    }
+
+    fn to_f64(&self, value: &u64) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &AllocationsFormatter
+    }
+}
+
+struct AllocationsFormatter;
+
+impl ValueFormatter for AllocationsFormatter {
+    fn scale_values(&self, _typical_value: f64, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical_value: f64,
+        _throughput: &Throughput,
+        _values: &mut [f64],
+    ) -> &'static str {
+        "allocs"
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct OwnedEvent {
+    #[serde(rename = "Records")]
+    records: Vec<OwnedRecord>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OwnedRecord {
+    message_id: String,
+    receipt_handle: String,
+    body: String,
+    event_source: String,
+    #[serde(rename = "eventSourceARN")]
+    event_source_arn: String,
+    aws_region: String,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+struct BorrowedEvent<'a> {
+    #[serde(rename = "Records", borrow)]
+    records: Vec<BorrowedRecord<'a>>,
+}
+
+#[allow(dead_code)]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BorrowedRecord<'a> {
+    message_id: &'a str,
+    receipt_handle: &'a str,
+    body: &'a str,
+    event_source: &'a str,
+    #[serde(rename = "eventSourceARN")]
+    event_source_arn: &'a str,
+    aws_region: &'a str,
+}
+
+// borrowing only works for strings without escapes, which covers most event fields
+fn sqs_payload() -> Payload {
+    let record = r#"{
+        "messageId": "059f36b4-87a3-44ab-83d2-661975830a7d",
+        "receiptHandle": "AQEBwJnKyrHigUMZj6rYigCgxlaS3SLy0a",
+        "body": "test_order 2",
+        "eventSource": "aws:sqs",
+        "eventSourceARN": "arn:aws:sqs:us-east-2:123456789012:test-queue",
+        "awsRegion": "us-east-2"
+    }"#;
+    let records = [record; 10].join(",");
+
+    Payload::from(Bytes::from(format!(r#"{{"Records": [{}]}}"#, records)))
+}
+
+fn payload<M: Measurement>(group: &mut BenchmarkGroup<'_, M>, runtime: &Runtime) {
+    let payload = sqs_payload();
+
+    group.bench_function("payload/owned", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(payload.parse::<OwnedEvent>())
+                .unwrap()
+                .records
+                .len()
+        })
+    });
+    group.bench_function("payload/borrowed", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(payload.parse::<BorrowedEvent>())
+                .unwrap()
+                .records
+                .len()
+        })
+    });
+}
+
+// the next and response requests every invocation makes, with uris precomputed by the api
+fn round_trip<M: Measurement>(group: &mut BenchmarkGroup<'_, M>, runtime: &Runtime, kaon: &Kaon) {
+    group.bench_function("round_trip", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let event = kaon.api.runtime_next_invocation().await.unwrap();
+                let event = hyper::body::to_bytes(event.into_body()).await.unwrap();
+
+                kaon.api
+                    .runtime_invocation_response(REQUEST_ID, Body::from(event))
+                    .await
+                    .unwrap();
+            })
+        })
+    });
+}
+
+async fn count_records(event: OwnedEvent, _context: Context) -> Result<usize, ()> {
+    Ok(event.records.len())
+}
+
+// the whole per-invocation loop through decay, polling, extracting, handling, hooks and
+// history, each batch serves one event under a fresh request id and then fails the next
+// poll so decay returns, mocks are swapped outside the measurement
+fn decay<M: Measurement>(
+    group: &mut BenchmarkGroup<'_, M>,
+    runtime: &Runtime,
+    server: &mut ServerGuard,
+    kaon: &mut Kaon,
+) {
+    let mut invocations = 0;
+    let mut served: Option<(Mock, Mock)> = None;
+
+    group.bench_function("decay", |b| {
+        b.iter_batched(
+            || {
+                if let Some((next, halt)) = served.take() {
+                    next.remove();
+                    halt.remove();
+                }
+                invocations += 1;
+
+                let next = server
+                    .mock("GET", "/2018-06-01/runtime/invocation/next")
+                    .with_header(
+                        "Lambda-Runtime-Aws-Request-Id",
+                        &format!("8476a536-e9f4-11e8-9739-{:012}", invocations),
+                    )
+                    .with_header("Lambda-Runtime-Deadline-Ms", "4102444800000")
+                    .with_body(sqs_payload().bytes().clone())
+                    .expect(1)
+                    .create();
+                let halt = server
+                    .mock("GET", "/2018-06-01/runtime/invocation/next")
+                    .with_status(500)
+                    .create();
+                served = Some((next, halt));
+            },
+            |()| runtime.block_on(kaon.decay(count_records)),
+            BatchSize::PerIteration,
+        )
+    });
+
+    if let Some((next, halt)) = served.take() {
+        next.remove();
+        halt.remove();
+    }
+}
+
+fn hot_path(c: &mut Criterion) {
+    let mut server = mockito::Server::new();
+    let _next = server
+        .mock("GET", "/2018-06-01/runtime/invocation/next")
+        .with_header("Lambda-Runtime-Aws-Request-Id", REQUEST_ID)
+        .with_body(sqs_payload().bytes().clone())
+        .expect_at_least(1)
+        .create();
+    let _response = server
+        .mock(
+            "POST",
+            Matcher::Regex(String::from(
+                r"^/2018-06-01/runtime/invocation/[0-9a-f-]+/response$",
+            )),
+        )
+        .expect_at_least(1)
+        .create();
+    std::env::set_var("AWS_LAMBDA_RUNTIME_API", server.host_with_port());
+
+    let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+    let mut kaon = runtime.block_on(async {
+        let mut kaon = Kaon::charge().await;
+        kaon.configure_api(ApiConfig::default()).await;
+        kaon
+    });
+
+    let mut group = c.benchmark_group("hot_path");
+    payload(&mut group, &runtime);
+    round_trip(&mut group, &runtime, &kaon);
+    decay(&mut group, &runtime, &mut server, &mut kaon);
+    group.finish();
+
+    let mut allocations = Criterion::default()
+        .with_measurement(Allocations)
+        .sample_size(10)
+        .configure_from_args();
+    let mut group = allocations.benchmark_group("hot_path_allocations");
+    payload(&mut group, &runtime);
+    round_trip(&mut group, &runtime, &kaon);
+    decay(&mut group, &runtime, &mut server, &mut kaon);
+    group.finish();
+}
+
+criterion_group!(benches, hot_path);
+criterion_main!(benches);
@@ -0,0 +1,77 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use hyper::Body;
+use kaon::{ApiConfig, Kaon};
+use tokio::runtime::{Builder, Runtime};
+
+const REQUEST_ID: &str = "8476a536-e9f4-11e8-9739-2dfe598c3fcd";
+
+// every invocation pays for fetching the next event and posting its response, this measures
+// that round trip against a local mock of the runtime api with the client tuned differently
+async fn round_trip(kaon: &Kaon) {
+    let event = kaon.api.runtime_next_invocation().await.unwrap();
+    let event = hyper::body::to_bytes(event.into_body()).await.unwrap();
+
+    kaon.api
+        .runtime_invocation_response(REQUEST_ID, Body::from(event))
+        .await
+        .unwrap();
+}
+
+fn charge(runtime: &Runtime, config: ApiConfig) -> Kaon {
+    runtime.block_on(async {
+        let mut kaon = Kaon::charge().await;
+        kaon.configure_api(config).await;
+        kaon
+    })
+}
+
+fn next_response_round_trip(c: &mut Criterion) {
+    let mut server = mockito::Server::new();
+    let _next = server
+        .mock("GET", "/2018-06-01/runtime/invocation/next")
+        .with_header("Lambda-Runtime-Aws-Request-Id", REQUEST_ID)
+        .with_header("Lambda-Runtime-Deadline-Ms", "1542409706888")
+        .with_body(r#"{"test_request": "hello"}"#)
+        .expect_at_least(1)
+        .create();
+    let _response = server
+        .mock(
+            "POST",
+            format!("/2018-06-01/runtime/invocation/{}/response", REQUEST_ID).as_str(),
+        )
+        .expect_at_least(1)
+        .create();
+    std::env::set_var("AWS_LAMBDA_RUNTIME_API", server.host_with_port());
+
+    let runtime = Builder::new_current_thread().enable_all().build().unwrap();
+    let configs = [
+        ("default", ApiConfig::default()),
+        (
+            "nagle",
+            ApiConfig {
+                tcp_nodelay: false,
+                ..ApiConfig::default()
+            },
+        ),
+        (
+            "unpooled",
+            ApiConfig {
+                pool_max_idle_per_host: 0,
+                ..ApiConfig::default()
+            },
+        ),
+    ];
+
+    let mut group = c.benchmark_group("next_response_round_trip");
+    group.sample_size(20);
+
+    for (name, config) in configs {
+        let kaon = charge(&runtime, config);
+        group.bench_function(name, |b| b.iter(|| runtime.block_on(round_trip(&kaon))));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, next_response_round_trip);
+criterion_main!(benches);
@@ -11,15 +11,139 @@ use hyper::http::uri::Scheme;
 // use hyper::Uri;
 use hyper::{HeaderMap, Request, Response, Uri};
 use std::ffi::OsString;
+use std::time::Duration;
 use tracing::{error, info, instrument};
 
+use crate::core::error::ErrorType;
+use crate::core::trace::TraceHeader;
+
+#[derive(Clone, Debug)]
+pub struct ApiConfig {
+    pub connect_timeout: Option<Duration>,
+    // applies to posting responses and errors, never to the long polled next invocation
+    pub response_timeout: Option<Duration>,
+    // frozen sandboxes can sit idle for minutes, so pooled connections never expire by default
+    pub pool_idle_timeout: Option<Duration>,
+    pub pool_max_idle_per_host: usize,
+    pub tcp_nodelay: bool,
+    pub tcp_keepalive: Option<Duration>,
+    pub http1_title_case_headers: bool,
+    // hyper rejects buffers smaller than 8192 bytes
+    pub http1_max_buf_size: Option<usize>,
+    pub http1_writev: Option<bool>,
+}
+
+impl Default for ApiConfig {
+    fn default() -> ApiConfig {
+        ApiConfig {
+            connect_timeout: None,
+            response_timeout: None,
+            pool_idle_timeout: None,
+            pool_max_idle_per_host: usize::MAX,
+            tcp_nodelay: true,
+            tcp_keepalive: None,
+            http1_title_case_headers: false,
+            http1_max_buf_size: None,
+            http1_writev: None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Api {
     pub client: Client<HttpConnector, Body>,
-    pub runtime_api: String,
+    pub response_timeout: Option<Duration>,
+    runtime_api: String,
+    // built once, only the request id changes between invocation uris
+    base_uri: String,
+    next_uri: Uri,
+    init_error_uri: Uri,
 }
 
 impl Api {
+    pub async fn create(runtime_api: String, config: &ApiConfig) -> Api {
+        let mut connector = HttpConnector::new();
+        connector.set_connect_timeout(config.connect_timeout);
+        connector.set_nodelay(config.tcp_nodelay);
+        connector.set_keepalive(config.tcp_keepalive);
+
+        let mut builder = Client::builder();
+        builder
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .http1_title_case_headers(config.http1_title_case_headers);
+
+        if let Some(max_buf_size) = config.http1_max_buf_size {
+            builder.http1_max_buf_size(max_buf_size);
+        }
+
+        if let Some(writev) = config.http1_writev {
+            builder.http1_writev(writev);
+        }
+
+        info!("| kaon api | client configured - {:?}", config);
+
+        Api {
+            client: builder.build(connector),
+            response_timeout: config.response_timeout,
+            base_uri: format!("http://{}/2018-06-01", runtime_api),
+            next_uri: Self::build_uri(&runtime_api, "/runtime/invocation/next").await,
+            init_error_uri: Self::build_uri(&runtime_api, "/runtime/init/error").await,
+            runtime_api,
+        }
+    }
+
+    pub fn runtime_api(&self) -> &str {
+        &self.runtime_api
+    }
+
+    pub async fn set_runtime_api(&mut self, runtime_api: String) {
+        self.base_uri = format!("http://{}/2018-06-01", runtime_api);
+        self.next_uri = Self::build_uri(&runtime_api, "/runtime/invocation/next").await;
+        self.init_error_uri = Self::build_uri(&runtime_api, "/runtime/init/error").await;
+        self.runtime_api = runtime_api;
+    }
+
+    fn invocation_uri(&self, request_id: &str, action: &str) -> Uri {
+        let mut uri = String::with_capacity(
+            self.base_uri.len() + "/runtime/invocation//".len() + request_id.len() + action.len(),
+        );
+        uri.push_str(&self.base_uri);
+        uri.push_str("/runtime/invocation/");
+        uri.push_str(request_id);
+        uri.push('/');
+        uri.push_str(action);
+
+        match Uri::try_from(uri) {
+            Ok(built_uri) => built_uri,
+            Err(error) => {
+                error!("| kaon uri | {}", error);
+                panic!("cannot build uri");
+            }
+        }
+    }
+
+    async fn send(&self, request: Request<Body>) -> Result<Response<Body>, String> {
+        let response = self.client.request(request);
+
+        let response = match self.response_timeout {
+            Some(response_timeout) => {
+                match tokio::time::timeout(response_timeout, response).await {
+                    Ok(response) => response,
+                    Err(_) => {
+                        return Err(format!(
+                            "no response from the runtime api within {:?}",
+                            response_timeout,
+                        ))
+                    }
+                }
+            }
+            None => response.await,
+        };
+
+        response.map_err(|error| format!("{:?}", error))
+    }
+
     #[instrument]
     async fn build_uri(authority: &str, path: &str) -> hyper::Uri {
         let uri = Uri::builder()
@@ -39,7 +163,7 @@ impl Api {
         }
     }
 
-    #[instrument]
+    #[instrument(skip(body))]
     pub async fn body_to_bytes(body: Body) -> Bytes {
         let body_bytes = hyper::body::to_bytes(body).await;
         match body_bytes {
@@ -54,37 +178,72 @@ impl Api {
         }
     }
 
-    #[instrument]
-    pub async fn get_header(header_map: &HeaderMap, key: &str) -> String {
+    // borrowed from the response headers, callers copy only what outlives the response
+    pub async fn get_header<'a>(header_map: &'a HeaderMap, key: &'a str) -> &'a str {
         let header_value = header_map.get(key);
         match header_value {
             Some(value) => {
                 let value_to_str = value.to_str();
                 match value_to_str {
-                    Ok(valid_header_value) => valid_header_value.to_string(),
+                    Ok(valid_header_value) => valid_header_value,
                     Err(invalid_header_value) => panic!("{}", invalid_header_value),
                 }
             }
             // None => panic!("{} is not found in response", key),
-            None => key.to_string(),
+            None => key,
         }
     }
 
-    #[instrument]
+    pub async fn get_trace_header(header_map: &HeaderMap) -> Option<TraceHeader> {
+        match header_map.get("Lambda-Runtime-Trace-Id") {
+            Some(value) => match value.to_str() {
+                Ok(valid_header_value) => TraceHeader::parse(valid_header_value).await,
+                Err(invalid_header_value) => {
+                    error!("| kaon api | {}", invalid_header_value);
+                    None
+                }
+            },
+            None => None,
+        }
+    }
+
+    pub async fn get_deadline(header_map: &HeaderMap) -> Option<u64> {
+        match header_map.get("Lambda-Runtime-Deadline-Ms") {
+            Some(value) => match value.to_str().map(str::parse::<u64>) {
+                Ok(Ok(deadline_ms)) => Some(deadline_ms),
+                _ => {
+                    error!("| kaon api | invalid deadline {:?}", value);
+                    None
+                }
+            },
+            None => None,
+        }
+    }
+
+    // std::env::set_var is process-wide, so this is only safe while invocations never overlap
     pub async fn set_tracing_header(header: &HeaderMap<HeaderValue>) {
-        if header.contains_key("Lambda-Runtime-Trace-Id") {
-            let x_amzn_trace_id = OsString::from("_X_AMZN_TRACE_ID");
-            let value = Api::get_header(header, "Lambda-Runtime-Trace-Id").await;
-            std::env::set_var(x_amzn_trace_id, OsString::from(&value));
+        let x_amzn_trace_id = OsString::from("_X_AMZN_TRACE_ID");
+
+        match header
+            .get("Lambda-Runtime-Trace-Id")
+            .map(HeaderValue::to_str)
+        {
+            Some(Ok(value)) => {
+                std::env::set_var(x_amzn_trace_id, value);
+                info!("| kaon api | _X_AMZN_TRACE_ID environment variable set");
+            }
+            Some(Err(invalid_header_value)) => {
+                std::env::remove_var(x_amzn_trace_id);
+                error!("| kaon api | {}", invalid_header_value);
+            }
+            // a trace from the previous invocation must not leak into this one
+            None => std::env::remove_var(x_amzn_trace_id),
         }
-        info!("| kaon api | _X_AMZN_TRACE_ID environment variable set");
     }
 
-    #[instrument]
+    #[instrument(skip(self))]
     pub async fn runtime_next_invocation(&self) -> Result<Response<Body>, hyper::Error> {
-        let path = "/runtime/invocation/next";
-        let uri = Self::build_uri(&self.runtime_api, path).await;
-        let response = self.client.get(uri).await;
+        let response = self.client.get(self.next_uri.clone()).await;
 
         match response {
             Ok(event) => {
@@ -98,70 +257,82 @@ impl Api {
         }
     }
 
-    #[instrument]
+    #[instrument(skip(self, response))]
     pub async fn runtime_invocation_response(
         &self,
         request_id: &str,
         response: Body,
     ) -> Result<(), hyper::http::Error> {
-        let path = format!("/runtime/invocation/{}/response", request_id);
-        let uri = Self::build_uri(&self.runtime_api, &path).await;
+        let uri = self.invocation_uri(request_id, "response");
         let request = Request::builder()
             .method("POST")
             .uri(uri)
             .body(response)
             .unwrap();
-        let response = &self.client.request(request).await;
+        let response = self.send(request).await;
 
         match &response {
             Ok(event) => {
                 info!("| kaon api | response {:?}", event.status());
             }
-            Err(error) => error!("| kaon api | {:?}", error),
+            Err(error) => error!("| kaon api | {}", error),
         }
         Ok(())
     }
 
-    #[instrument]
-    pub async fn runtime_invocation_error(&self, request_id: &str, error: Body) {
-        let path = format!("/runtime/invocation/{}/error", request_id);
-        let uri = Self::build_uri(&self.runtime_api, &path).await;
-        let request = Request::builder()
+    #[instrument(skip(self, xray_error_cause, error))]
+    pub async fn runtime_invocation_error(
+        &self,
+        request_id: &str,
+        error_type: &ErrorType,
+        xray_error_cause: Option<&str>,
+        error: Body,
+    ) {
+        let uri = self.invocation_uri(request_id, "error");
+        let mut request = Request::builder()
             .method("POST")
-            .header("Lambda-Runtime-Function-Error-Type", "Unhandled")
-            .uri(uri)
-            .body(error)
-            .unwrap();
-        let response = &self.client.request(request).await;
+            .header("Lambda-Runtime-Function-Error-Type", error_type.as_str());
+
+        if let Some(cause) = xray_error_cause {
+            match HeaderValue::from_str(cause) {
+                Ok(value) => {
+                    request = request.header("Lambda-Runtime-Function-XRay-Error-Cause", value);
+                }
+                Err(error) => error!("| kaon api | cannot send x-ray error cause - {}", error),
+            }
+        }
+
+        let request = request.uri(uri).body(error).unwrap();
+        let response = self.send(request).await;
 
         match &response {
             Ok(event) => {
                 info!("| kaon api | error sent {:?}", event.status());
             }
-            Err(error) => error!("| kaon api | {:?}", error),
+            Err(error) => error!("| kaon api | {}", error),
         }
     }
 
-    #[instrument]
+    #[instrument(skip(self, error))]
     pub async fn runtime_initialization_error(
         &self,
+        error_type: &ErrorType,
         error: Body,
     ) -> Result<(), hyper::http::Error> {
-        let path = "/runtime/init/error";
-        let uri = Self::build_uri(&self.runtime_api, path).await;
+        let uri = self.init_error_uri.clone();
         let request = Request::builder()
             .method("POST")
-            .header("Lambda-Runtime-Function-Error-Type", "Unhandled")
+            .header("Lambda-Runtime-Function-Error-Type", error_type.as_str())
             .uri(uri)
             .body(error)
             .unwrap();
-        let response = &self.client.request(request).await;
+        let response = self.send(request).await;
 
         match &response {
             Ok(event) => {
                 info!("| kaon api | error sent {:?}", event.status())
             }
-            Err(error) => error!("| kaon api | {:?}", error),
+            Err(error) => error!("| kaon api | {}", error),
         }
         Ok(())
     }
@@ -182,6 +353,32 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn invocation_uri() {
+        let mut test_api = Api::create(String::from("127.0.0.1:9001"), &ApiConfig::default()).await;
+        assert_eq!(
+            test_api.invocation_uri("156cb537-e2d4-11e8-9b34-d36013741fb9", "response"),
+            "http://127.0.0.1:9001/2018-06-01/runtime/invocation/156cb537-e2d4-11e8-9b34-d36013741fb9/response",
+        );
+        assert_eq!(
+            test_api.next_uri,
+            "http://127.0.0.1:9001/2018-06-01/runtime/invocation/next",
+        );
+
+        test_api
+            .set_runtime_api(String::from("localhost:9002"))
+            .await;
+        assert_eq!(test_api.runtime_api(), "localhost:9002");
+        assert_eq!(
+            test_api.init_error_uri,
+            "http://localhost:9002/2018-06-01/runtime/init/error",
+        );
+        assert_eq!(
+            test_api.invocation_uri("test_request_id", "error"),
+            "http://localhost:9002/2018-06-01/runtime/invocation/test_request_id/error",
+        );
+    }
+
     #[tokio::test]
     async fn body_to_bytes() {
         let test_body = Body::from("test");
@@ -207,12 +404,55 @@ mod tests {
         let test_get_header = Api::get_header(&test_headers, "Lambda-Runtime-Trace-Id").await;
         assert_eq!(
             test_get_header,
-            String::from(
-                "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1"
-            )
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+        );
+        assert_eq!(
+            Api::get_header(&test_headers, "Lambda-Runtime-Client-Context").await,
+            "Lambda-Runtime-Client-Context",
         );
     }
 
+    #[tokio::test]
+    async fn get_trace_header() {
+        let mut test_headers = HeaderMap::new();
+        assert!(Api::get_trace_header(&test_headers).await.is_none());
+        test_headers.insert(
+            "Lambda-Runtime-Trace-Id",
+            HeaderValue::from_static(
+                "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+            ),
+        );
+        let test_trace_header = Api::get_trace_header(&test_headers).await.unwrap();
+        assert_eq!(
+            test_trace_header.root,
+            String::from("1-5759e988-bd862e3fe1be46a994272793"),
+        );
+        assert_eq!(
+            test_trace_header.parent,
+            Some(String::from("53995c3f42cd8ad8")),
+        );
+        assert_eq!(test_trace_header.sampled, Some(true));
+        assert_eq!(test_trace_header.lineage, None);
+    }
+
+    #[tokio::test]
+    async fn get_deadline() {
+        let mut test_headers = HeaderMap::new();
+        assert_eq!(Api::get_deadline(&test_headers).await, None);
+
+        test_headers.insert(
+            "Lambda-Runtime-Deadline-Ms",
+            HeaderValue::from_static("1542409706888"),
+        );
+        assert_eq!(Api::get_deadline(&test_headers).await, Some(1542409706888));
+
+        test_headers.insert(
+            "Lambda-Runtime-Deadline-Ms",
+            HeaderValue::from_static("test_deadline"),
+        );
+        assert_eq!(Api::get_deadline(&test_headers).await, None);
+    }
+
     #[tokio::test]
     async fn set_tracing_header() {
         let mut test_headers = HeaderMap::new();
@@ -231,21 +471,21 @@ mod tests {
         Api::set_tracing_header(&test_headers).await;
         assert!(std::env::var_os(&test_environment_variable).is_some());
         assert_eq!(
-            std::env::var_os(test_environment_variable),
+            std::env::var_os(&test_environment_variable),
             Some(OsString::from(
                 "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1"
             )),
         );
+
+        Api::set_tracing_header(&HeaderMap::new()).await;
+        assert!(std::env::var_os(test_environment_variable).is_none());
     }
 
     #[tokio::test]
     async fn runtime_next_invocation() -> Result<(), hyper::Error> {
-        let mut test_server = mockito::Server::new();
+        let mut test_server = mockito::Server::new_async().await;
         let test_runtime_api = test_server.host_with_port();
-        let test_api = Api {
-            client: Client::new(),
-            runtime_api: test_runtime_api,
-        };
+        let test_api = Api::create(test_runtime_api, &ApiConfig::default()).await;
         let mock = test_server
             .mock("GET", "/2018-06-01/runtime/invocation/next")
             .with_status(200)
@@ -340,12 +580,9 @@ mod tests {
 
     #[tokio::test]
     async fn runtime_invocation_response() -> Result<(), hyper::http::Error> {
-        let mut test_server = mockito::Server::new();
+        let mut test_server = mockito::Server::new_async().await;
         let test_runtime_api = test_server.host_with_port();
-        let test_api = Api {
-            client: Client::new(),
-            runtime_api: test_runtime_api,
-        };
+        let test_api = Api::create(test_runtime_api, &ApiConfig::default()).await;
         let test_request_id = String::from("156cb537-e2d4-11e8-9b34-d36013741fb9");
         let test_body = Body::from("SUCCESS");
         let mock = test_server
@@ -363,12 +600,9 @@ mod tests {
 
     #[tokio::test]
     async fn runtime_invocation_error() {
-        let mut test_server = mockito::Server::new();
+        let mut test_server = mockito::Server::new_async().await;
         let test_runtime_api = test_server.host_with_port();
-        let test_api = Api {
-            client: Client::new(),
-            runtime_api: test_runtime_api,
-        };
+        let test_api = Api::create(test_runtime_api, &ApiConfig::default()).await;
         let test_request_id = String::from("156cb537-e2d4-11e8-9b34-d36013741fb9");
         let test_error = Body::from(
             r#"{"errorMessage": "test_kaon_error_message", "errorType": "test_kaon_error_type"}"#,
@@ -377,31 +611,78 @@ mod tests {
             "POST",
             "/2018-06-01/runtime/invocation/156cb537-e2d4-11e8-9b34-d36013741fb9/error",
         )
+        .match_header("Lambda-Runtime-Function-Error-Type", "test_kaon_error_type")
+        .match_header("Lambda-Runtime-Function-XRay-Error-Cause", r#"{"exceptions":[]}"#)
         .match_body(
             r#"{"errorMessage": "test_kaon_error_message", "errorType": "test_kaon_error_type"}"#,
         )
         .create();
-        Api::runtime_invocation_error(&test_api, &test_request_id, test_error).await;
+        let test_error_type = ErrorType::Custom(String::from("test_kaon_error_type"));
+        Api::runtime_invocation_error(
+            &test_api,
+            &test_request_id,
+            &test_error_type,
+            Some(r#"{"exceptions":[]}"#),
+            test_error,
+        )
+        .await;
         mock.assert();
         assert!(mock.matched());
     }
 
+    #[tokio::test]
+    async fn create() {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_config = ApiConfig {
+            connect_timeout: Some(Duration::from_millis(250)),
+            response_timeout: Some(Duration::from_secs(1)),
+            pool_idle_timeout: Some(Duration::from_secs(30)),
+            pool_max_idle_per_host: 1,
+            tcp_nodelay: false,
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            http1_title_case_headers: true,
+            http1_max_buf_size: Some(16384),
+            http1_writev: Some(false),
+        };
+        let test_api = Api::create(test_server.host_with_port(), &test_config).await;
+        assert_eq!(test_api.response_timeout, Some(Duration::from_secs(1)));
+
+        let mock = test_server
+            .mock(
+                "POST",
+                "/2018-06-01/runtime/invocation/156cb537-e2d4-11e8-9b34-d36013741fb9/response",
+            )
+            .match_body("SUCCESS")
+            .expect(2)
+            .create_async()
+            .await;
+
+        for _ in 0..2 {
+            test_api
+                .runtime_invocation_response(
+                    "156cb537-e2d4-11e8-9b34-d36013741fb9",
+                    Body::from("SUCCESS"),
+                )
+                .await
+                .unwrap();
+        }
+        mock.assert_async().await;
+    }
+
     #[tokio::test]
     async fn runtime_initialization_error() -> Result<(), hyper::http::Error> {
-        let mut test_server = mockito::Server::new();
+        let mut test_server = mockito::Server::new_async().await;
         let test_runtime_api = test_server.host_with_port();
-        let test_api = Api {
-            client: Client::new(),
-            runtime_api: test_runtime_api,
-        };
+        let test_api = Api::create(test_runtime_api, &ApiConfig::default()).await;
         let test_error = Body::from(
             r#"{"errorMessage": "test_kaon_error_message", "errorType": "test_kaon_error_type"}"#,
         );
         let mock = test_server
             .mock("POST", "/2018-06-01/runtime/init/error")
+            .match_header("Lambda-Runtime-Function-Error-Type", "Unhandled")
             .match_body(r#"{"errorMessage": "test_kaon_error_message", "errorType": "test_kaon_error_type"}"#)
             .create();
-        Api::runtime_initialization_error(&test_api, test_error).await?;
+        Api::runtime_initialization_error(&test_api, &ErrorType::Unhandled, test_error).await?;
         mock.assert();
         assert!(mock.matched());
         Ok(())
@@ -0,0 +1,365 @@
+use hyper::body::{Body, Bytes};
+use hyper::client::connect::HttpConnector;
+use hyper::client::Client;
+use hyper::{Method, Request, Response, Uri};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use serde_json::Value;
+use std::fmt;
+use tracing::{error, info, instrument};
+
+use crate::core::invocation::Invocation;
+use crate::core::sigv4::{Signer, SigningError};
+
+#[cfg(feature = "dynamodb")]
+pub mod dynamodb;
+#[cfg(feature = "s3")]
+pub mod s3;
+#[cfg(feature = "sns")]
+pub mod sns;
+#[cfg(feature = "sqs")]
+pub mod sqs;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AwsError {
+    Signing(SigningError),
+    InvalidRequest(String),
+    Transport(String),
+    Service {
+        status: u16,
+        code: Option<String>,
+        message: String,
+    },
+    Unmarshal(String),
+}
+
+impl fmt::Display for AwsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AwsError::Signing(error) => write!(f, "{}", error),
+            AwsError::InvalidRequest(error) => write!(f, "invalid request - {}", error),
+            AwsError::Transport(error) => write!(f, "transport error - {}", error),
+            AwsError::Service {
+                status,
+                code: Some(code),
+                message,
+            } => write!(f, "{} ({}) - {}", code, status, message),
+            AwsError::Service {
+                status, message, ..
+            } => write!(f, "service error ({}) - {}", status, message),
+            AwsError::Unmarshal(error) => write!(f, "unexpected response - {}", error),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct AwsClient {
+    client: Client<HttpsConnector<HttpConnector>, Body>,
+    signer: Signer,
+    endpoint: String,
+}
+
+impl AwsClient {
+    pub async fn create(signer: Signer) -> AwsClient {
+        let connector = HttpsConnectorBuilder::new()
+            .with_webpki_roots()
+            .https_or_http()
+            .enable_http1()
+            .build();
+        let endpoint = format!(
+            "https://{}.{}.amazonaws.com",
+            signer.service(),
+            signer.region(),
+        );
+
+        AwsClient {
+            client: Client::builder().build(connector),
+            signer,
+            endpoint,
+        }
+    }
+
+    // for VPC endpoints and local mocks
+    pub async fn with_endpoint(mut self, endpoint: &str) -> Result<AwsClient, AwsError> {
+        let uri = endpoint
+            .parse::<Uri>()
+            .map_err(|error| AwsError::InvalidRequest(error.to_string()))?;
+
+        if uri.scheme().is_none() || uri.authority().is_none() {
+            return Err(AwsError::InvalidRequest(format!(
+                "{} is not an absolute endpoint",
+                endpoint,
+            )));
+        }
+
+        self.endpoint = endpoint.trim_end_matches('/').to_string();
+        Ok(self)
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    // signs and sends any request, for services without a typed client
+    #[instrument(skip(self, headers, body))]
+    pub async fn send(
+        &self,
+        method: Method,
+        path_and_query: &str,
+        headers: &[(&str, &str)],
+        body: Bytes,
+    ) -> Result<Response<Bytes>, AwsError> {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(format!("{}{}", self.endpoint, path_and_query));
+
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+
+        let request = request
+            .body(body)
+            .map_err(|error| AwsError::InvalidRequest(error.to_string()))?;
+        let mut request = self.signer.sign(request).await.map_err(AwsError::Signing)?;
+
+        // added after signing, as the SDKs do, so the signature never covers the trace header
+        if let Some(trace_header) =
+            Invocation::with_current(|invocation| invocation.trace_header.clone()).flatten()
+        {
+            trace_header.propagate(request.headers_mut()).await;
+        }
+
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|error| AwsError::Transport(error.to_string()))?;
+        let (parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body)
+            .await
+            .map_err(|error| AwsError::Transport(error.to_string()))?;
+
+        match parts.status.is_success() {
+            true => {
+                info!(
+                    "| kaon aws | {} responded {}",
+                    self.signer.service(),
+                    parts.status,
+                );
+                Ok(Response::from_parts(parts, body))
+            }
+            false => {
+                let error = service_error(parts.status.as_u16(), &body);
+                error!("| kaon aws | {} - {}", self.signer.service(), error);
+                Err(error)
+            }
+        }
+    }
+
+    // the AWS JSON 1.0 protocol used by SQS and DynamoDB
+    #[cfg(any(feature = "sqs", feature = "dynamodb"))]
+    pub(crate) async fn send_json(&self, target: &str, payload: &Value) -> Result<Value, AwsError> {
+        let body = serde_json::to_vec(payload)
+            .map_err(|error| AwsError::InvalidRequest(error.to_string()))?;
+        let headers = [
+            ("content-type", "application/x-amz-json-1.0"),
+            ("x-amz-target", target),
+        ];
+
+        let response = self
+            .send(Method::POST, "/", &headers, Bytes::from(body))
+            .await?;
+
+        serde_json::from_slice(response.body())
+            .map_err(|error| AwsError::Unmarshal(error.to_string()))
+    }
+}
+
+impl fmt::Debug for AwsClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsClient")
+            .field("signer", &self.signer)
+            .field("endpoint", &self.endpoint)
+            .finish()
+    }
+}
+
+// JSON protocols report {"__type": "...#Code", "message": "..."}, query and REST protocols
+// report <Code> and <Message> elements, both can come back through send
+fn service_error(status: u16, body: &[u8]) -> AwsError {
+    if let Ok(error) = serde_json::from_slice::<Value>(body) {
+        let code = error
+            .get("__type")
+            .or_else(|| error.get("code"))
+            .and_then(Value::as_str)
+            .map(|code| code.rsplit('#').next().unwrap_or(code).to_string());
+        let message = error
+            .get("message")
+            .or_else(|| error.get("Message"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+
+        return AwsError::Service {
+            status,
+            code,
+            message,
+        };
+    }
+
+    let body = String::from_utf8_lossy(body);
+
+    AwsError::Service {
+        status,
+        code: xml_element(&body, "Code"),
+        message: xml_element(&body, "Message").unwrap_or_default(),
+    }
+}
+
+pub(crate) fn xml_element(document: &str, name: &str) -> Option<String> {
+    let open = format!("<{}>", name);
+    let close = format!("</{}>", name);
+    let start = document.find(&open)? + open.len();
+    let end = start + document[start..].find(&close)?;
+
+    Some(
+        document[start..end]
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&apos;", "'")
+            .replace("&amp;", "&"),
+    )
+}
+
+#[cfg(test)]
+pub(crate) async fn test_client(service: &str, endpoint: &str) -> AwsClient {
+    let test_credentials = crate::core::credentials::Credentials::create(
+        "AKIDEXAMPLE",
+        "test_secret_access_key",
+        Some("test_session_token"),
+    )
+    .await;
+    let test_signer = Signer::create(test_credentials, "us-east-1", service).await;
+
+    AwsClient::create(test_signer)
+        .await
+        .with_endpoint(endpoint)
+        .await
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn service_errors() {
+        assert_eq!(
+            service_error(
+                400,
+                br#"{"__type": "com.amazonaws.dynamodb.v20120810#ResourceNotFoundException", "message": "Requested resource not found"}"#,
+            ),
+            AwsError::Service {
+                status: 400,
+                code: Some(String::from("ResourceNotFoundException")),
+                message: String::from("Requested resource not found"),
+            },
+        );
+        assert_eq!(
+            service_error(
+                404,
+                b"<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+            )
+            .to_string(),
+            "NoSuchKey (404) - The specified key does not exist.",
+        );
+        assert_eq!(
+            xml_element("<Message>a &amp;&lt;b&gt;</Message>", "Message"),
+            Some(String::from("a &<b>")),
+        );
+    }
+
+    #[tokio::test]
+    async fn send() {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_client = test_client("lambda", &test_server.url()).await;
+
+        let mock = test_server
+            .mock("GET", "/2015-03-31/functions/test_function")
+            .match_header("x-amz-security-token", "test_session_token")
+            .match_header(
+                "authorization",
+                mockito::Matcher::Regex(String::from(
+                    r"^AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/\d{8}/us-east-1/lambda/aws4_request, ",
+                )),
+            )
+            .with_body(r#"{"Configuration": {}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_missing = test_server
+            .mock("GET", "/2015-03-31/functions/test_missing")
+            .with_status(404)
+            .with_body("<Error><Code>ResourceNotFoundException</Code><Message>Function not found</Message></Error>")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let test_response = test_client
+            .send(
+                Method::GET,
+                "/2015-03-31/functions/test_function",
+                &[],
+                Bytes::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(test_response.body().as_ref(), br#"{"Configuration": {}}"#);
+
+        let test_error = test_client
+            .send(
+                Method::GET,
+                "/2015-03-31/functions/test_missing",
+                &[],
+                Bytes::new(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            test_error,
+            AwsError::Service {
+                status: 404,
+                code: Some(String::from("ResourceNotFoundException")),
+                message: String::from("Function not found"),
+            },
+        );
+
+        mock.assert_async().await;
+        mock_missing.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn with_endpoint() {
+        let test_credentials = crate::core::credentials::Credentials::create(
+            "AKIDEXAMPLE",
+            "test_secret_access_key",
+            None,
+        )
+        .await;
+        let test_signer = Signer::create(test_credentials, "us-east-1", "sqs").await;
+        let test_client = AwsClient::create(test_signer).await;
+        assert_eq!(
+            test_client.endpoint(),
+            "https://sqs.us-east-1.amazonaws.com"
+        );
+
+        let test_client = test_client
+            .with_endpoint("http://127.0.0.1:4566/")
+            .await
+            .unwrap();
+        assert_eq!(test_client.endpoint(), "http://127.0.0.1:4566");
+        assert!(!format!("{:?}", test_client).contains("test_secret_access_key"));
+
+        assert!(test_client.with_endpoint("/queue").await.is_err());
+    }
+}
@@ -0,0 +1,119 @@
+use serde_json::{json, Value};
+
+use crate::core::aws::{AwsClient, AwsError};
+use crate::core::sigv4::Signer;
+
+// items and keys use the DynamoDB attribute value format, e.g. {"id": {"S": "test_id"}}
+#[derive(Clone, Debug)]
+pub struct DynamoDbClient {
+    client: AwsClient,
+}
+
+impl DynamoDbClient {
+    pub async fn create(signer: Signer) -> DynamoDbClient {
+        DynamoDbClient {
+            client: AwsClient::create(signer).await,
+        }
+    }
+
+    pub async fn with_endpoint(self, endpoint: &str) -> Result<DynamoDbClient, AwsError> {
+        Ok(DynamoDbClient {
+            client: self.client.with_endpoint(endpoint).await?,
+        })
+    }
+
+    pub async fn get_item(&self, table_name: &str, key: Value) -> Result<Option<Value>, AwsError> {
+        let payload = json!({
+            "TableName": table_name,
+            "Key": key,
+        });
+
+        let mut response = self
+            .client
+            .send_json("DynamoDB_20120810.GetItem", &payload)
+            .await?;
+
+        Ok(response.get_mut("Item").map(Value::take))
+    }
+
+    pub async fn put_item(&self, table_name: &str, item: Value) -> Result<(), AwsError> {
+        let payload = json!({
+            "TableName": table_name,
+            "Item": item,
+        });
+
+        self.client
+            .send_json("DynamoDB_20120810.PutItem", &payload)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::aws::test_client;
+
+    #[tokio::test]
+    async fn items() {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_item = json!({ "id": { "S": "test_id" }, "count": { "N": "1" } });
+
+        let mock_put = test_server
+            .mock("POST", "/")
+            .match_header("x-amz-target", "DynamoDB_20120810.PutItem")
+            .match_body(mockito::Matcher::Json(json!({
+                "TableName": "test-table",
+                "Item": test_item,
+            })))
+            .with_body("{}")
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_get = test_server
+            .mock("POST", "/")
+            .match_header("x-amz-target", "DynamoDB_20120810.GetItem")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "Key": { "id": { "S": "test_id" } },
+            })))
+            .with_body(json!({ "Item": test_item }).to_string())
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_missing = test_server
+            .mock("POST", "/")
+            .match_header("x-amz-target", "DynamoDB_20120810.GetItem")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "Key": { "id": { "S": "test_missing" } },
+            })))
+            .with_body("{}")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let test_dynamodb = DynamoDbClient {
+            client: test_client("dynamodb", &test_server.url()).await,
+        };
+
+        test_dynamodb
+            .put_item("test-table", test_item.clone())
+            .await
+            .unwrap();
+        mock_put.assert_async().await;
+
+        let test_response = test_dynamodb
+            .get_item("test-table", json!({ "id": { "S": "test_id" } }))
+            .await
+            .unwrap();
+        assert_eq!(test_response, Some(test_item));
+        mock_get.assert_async().await;
+
+        let test_response = test_dynamodb
+            .get_item("test-table", json!({ "id": { "S": "test_missing" } }))
+            .await
+            .unwrap();
+        assert_eq!(test_response, None);
+        mock_missing.assert_async().await;
+    }
+}
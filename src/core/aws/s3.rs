@@ -0,0 +1,144 @@
+use hyper::body::Bytes;
+use hyper::header::ETAG;
+use hyper::Method;
+
+use crate::core::aws::{AwsClient, AwsError};
+use crate::core::sigv4::{uri_encode, Signer};
+
+#[derive(Clone, Debug)]
+pub struct S3Client {
+    client: AwsClient,
+}
+
+impl S3Client {
+    pub async fn create(signer: Signer) -> S3Client {
+        S3Client {
+            client: AwsClient::create(signer).await,
+        }
+    }
+
+    pub async fn with_endpoint(self, endpoint: &str) -> Result<S3Client, AwsError> {
+        Ok(S3Client {
+            client: self.client.with_endpoint(endpoint).await?,
+        })
+    }
+
+    pub async fn get_object(&self, bucket: &str, key: &str) -> Result<Bytes, AwsError> {
+        let response = self
+            .client
+            .send(Method::GET, &object_path(bucket, key), &[], Bytes::new())
+            .await?;
+
+        Ok(response.into_body())
+    }
+
+    pub async fn put_object(
+        &self,
+        bucket: &str,
+        key: &str,
+        body: impl Into<Bytes>,
+        content_type: Option<&str>,
+    ) -> Result<Option<String>, AwsError> {
+        let headers: Vec<(&str, &str)> = content_type
+            .map(|content_type| ("content-type", content_type))
+            .into_iter()
+            .collect();
+
+        let response = self
+            .client
+            .send(
+                Method::PUT,
+                &object_path(bucket, key),
+                &headers,
+                body.into(),
+            )
+            .await?;
+
+        Ok(response
+            .headers()
+            .get(ETAG)
+            .and_then(|etag| etag.to_str().ok())
+            .map(str::to_string))
+    }
+}
+
+// path style addressing keeps custom endpoints and dotted bucket names working
+fn object_path(bucket: &str, key: &str) -> String {
+    format!("/{}/{}", uri_encode(bucket, true), uri_encode(key, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::aws::test_client;
+
+    #[tokio::test]
+    async fn objects() {
+        let mut test_server = mockito::Server::new_async().await;
+
+        let mock_put = test_server
+            .mock("PUT", "/test-bucket/test%20dir/test.json")
+            .match_header("content-type", "application/json")
+            .match_header(
+                "x-amz-content-sha256",
+                "80f65706d935d3b928d95207937dd81bad43ab56cd4d3b7ed41772318e734168",
+            )
+            .match_body(r#"{"test": true}"#)
+            .with_header("etag", "\"test_etag\"")
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_get = test_server
+            .mock("GET", "/test-bucket/test%20dir/test.json")
+            .match_header(
+                "authorization",
+                mockito::Matcher::Regex(String::from(r"/us-east-1/s3/aws4_request, ")),
+            )
+            .with_body(r#"{"test": true}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_missing = test_server
+            .mock("GET", "/test-bucket/test-missing")
+            .with_status(404)
+            .with_body(
+                "<Error><Code>NoSuchKey</Code><Message>The specified key does not exist.</Message></Error>",
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let test_s3 = S3Client {
+            client: test_client("s3", &test_server.url()).await,
+        };
+
+        let test_etag = test_s3
+            .put_object(
+                "test-bucket",
+                "test dir/test.json",
+                r#"{"test": true}"#,
+                Some("application/json"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(test_etag.as_deref(), Some("\"test_etag\""));
+        mock_put.assert_async().await;
+
+        let test_object = test_s3
+            .get_object("test-bucket", "test dir/test.json")
+            .await
+            .unwrap();
+        assert_eq!(test_object, Bytes::from_static(br#"{"test": true}"#));
+        mock_get.assert_async().await;
+
+        let test_error = test_s3
+            .get_object("test-bucket", "test-missing")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            test_error,
+            AwsError::Service { status: 404, code: Some(code), .. } if code == "NoSuchKey"
+        ));
+        mock_missing.assert_async().await;
+    }
+}
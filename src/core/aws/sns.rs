@@ -0,0 +1,109 @@
+use hyper::body::Bytes;
+use hyper::Method;
+
+use crate::core::aws::{xml_element, AwsClient, AwsError};
+use crate::core::sigv4::{uri_encode, Signer};
+
+#[derive(Clone, Debug)]
+pub struct SnsClient {
+    client: AwsClient,
+}
+
+impl SnsClient {
+    pub async fn create(signer: Signer) -> SnsClient {
+        SnsClient {
+            client: AwsClient::create(signer).await,
+        }
+    }
+
+    pub async fn with_endpoint(self, endpoint: &str) -> Result<SnsClient, AwsError> {
+        Ok(SnsClient {
+            client: self.client.with_endpoint(endpoint).await?,
+        })
+    }
+
+    pub async fn publish(
+        &self,
+        topic_arn: &str,
+        message: &str,
+        subject: Option<&str>,
+    ) -> Result<String, AwsError> {
+        let mut parameters = vec![
+            ("Action", "Publish"),
+            ("Version", "2010-03-31"),
+            ("TopicArn", topic_arn),
+            ("Message", message),
+        ];
+        if let Some(subject) = subject {
+            parameters.push(("Subject", subject));
+        }
+
+        // SNS only speaks the query protocol, form encoded requests with XML responses
+        let body = parameters
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, uri_encode(value, true)))
+            .collect::<Vec<String>>()
+            .join("&");
+        let headers = [(
+            "content-type",
+            "application/x-www-form-urlencoded; charset=utf-8",
+        )];
+
+        let response = self
+            .client
+            .send(Method::POST, "/", &headers, Bytes::from(body))
+            .await?;
+
+        xml_element(&String::from_utf8_lossy(response.body()), "MessageId")
+            .ok_or_else(|| AwsError::Unmarshal(String::from("Publish response has no MessageId")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::aws::test_client;
+
+    #[tokio::test]
+    async fn publish() {
+        let mut test_server = mockito::Server::new_async().await;
+
+        let mock = test_server
+            .mock("POST", "/")
+            .match_header(
+                "content-type",
+                "application/x-www-form-urlencoded; charset=utf-8",
+            )
+            .match_header(
+                "authorization",
+                mockito::Matcher::Regex(String::from(r"/us-east-1/sns/aws4_request, ")),
+            )
+            .match_body(
+                "Action=Publish&Version=2010-03-31\
+                 &TopicArn=arn%3Aaws%3Asns%3Aus-east-1%3A123456789012%3Atest-topic\
+                 &Message=test%20message&Subject=test_subject",
+            )
+            .with_body(
+                "<PublishResponse><PublishResult><MessageId>test_message_id</MessageId>\
+                 </PublishResult></PublishResponse>",
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let test_sns = SnsClient {
+            client: test_client("sns", &test_server.url()).await,
+        };
+
+        let test_message_id = test_sns
+            .publish(
+                "arn:aws:sns:us-east-1:123456789012:test-topic",
+                "test message",
+                Some("test_subject"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(test_message_id, "test_message_id");
+        mock.assert_async().await;
+    }
+}
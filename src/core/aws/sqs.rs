@@ -0,0 +1,123 @@
+use serde_json::{json, Value};
+
+use crate::core::aws::{AwsClient, AwsError};
+use crate::core::sigv4::Signer;
+
+#[derive(Clone, Debug)]
+pub struct SqsClient {
+    client: AwsClient,
+}
+
+impl SqsClient {
+    pub async fn create(signer: Signer) -> SqsClient {
+        SqsClient {
+            client: AwsClient::create(signer).await,
+        }
+    }
+
+    pub async fn with_endpoint(self, endpoint: &str) -> Result<SqsClient, AwsError> {
+        Ok(SqsClient {
+            client: self.client.with_endpoint(endpoint).await?,
+        })
+    }
+
+    pub async fn send_message(
+        &self,
+        queue_url: &str,
+        message_body: &str,
+    ) -> Result<String, AwsError> {
+        let payload = json!({
+            "QueueUrl": queue_url,
+            "MessageBody": message_body,
+        });
+
+        let response = self
+            .client
+            .send_json("AmazonSQS.SendMessage", &payload)
+            .await?;
+
+        match response.get("MessageId").and_then(Value::as_str) {
+            Some(message_id) => Ok(message_id.to_string()),
+            None => Err(AwsError::Unmarshal(String::from(
+                "SendMessage response has no MessageId",
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::aws::test_client;
+    use crate::core::invocation::Invocation;
+    use crate::core::trace::TraceHeader;
+
+    #[tokio::test]
+    async fn send_message() {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_queue_url = "https://sqs.us-east-1.amazonaws.com/123456789012/test-queue";
+
+        let mock = test_server
+            .mock("POST", "/")
+            .match_header("x-amz-target", "AmazonSQS.SendMessage")
+            .match_header("content-type", "application/x-amz-json-1.0")
+            .match_header("x-amz-security-token", "test_session_token")
+            .match_header(
+                "x-amzn-trace-id",
+                "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+            )
+            .match_header(
+                "authorization",
+                mockito::Matcher::Regex(String::from(
+                    r"^AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/\d{8}/us-east-1/sqs/aws4_request, ",
+                )),
+            )
+            .match_body(mockito::Matcher::Json(json!({
+                "QueueUrl": test_queue_url,
+                "MessageBody": "test_message",
+            })))
+            .with_body(r#"{"MessageId": "test_message_id", "MD5OfMessageBody": "test_md5"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_error = test_server
+            .mock("POST", "/")
+            .with_status(400)
+            .with_body(r#"{"__type": "com.amazonaws.sqs#QueueDoesNotExist", "message": "The specified queue does not exist."}"#)
+            .create_async()
+            .await;
+
+        let test_sqs = SqsClient {
+            client: test_client("sqs", &test_server.url()).await,
+        };
+
+        let test_invocation = Invocation {
+            aws_request_id: String::from("8476a536-e9f4-11e8-9739-2dfe598c3fcd"),
+            trace_header: TraceHeader::parse(
+                "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+            )
+            .await,
+            deadline_ms: None,
+        };
+        let test_message_id = test_invocation
+            .scope(test_sqs.send_message(test_queue_url, "test_message"))
+            .await
+            .unwrap();
+        assert_eq!(test_message_id, "test_message_id");
+        mock.assert_async().await;
+
+        let test_error = test_sqs
+            .send_message(test_queue_url, "test_message")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            test_error,
+            AwsError::Service {
+                status: 400,
+                code: Some(String::from("QueueDoesNotExist")),
+                message: String::from("The specified queue does not exist."),
+            },
+        );
+        mock_error.assert_async().await;
+    }
+}
@@ -1,4 +1,9 @@
+use crate::core::initialization::Initialization;
+use crate::core::metrics::Metrics;
+use crate::core::parameters::Parameters;
+use crate::core::trace::TraceHeader;
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Context {
@@ -6,6 +11,14 @@ pub struct Context {
     pub invoked_function_arn: String,
     pub identity: String,
     pub client_context: String,
+    pub trace_header: Option<TraceHeader>,
+    pub initialization: Initialization,
+    #[serde(default)]
+    pub deadline_ms: Option<u64>,
+    #[serde(skip)]
+    pub metrics: Metrics,
+    #[serde(skip)]
+    pub parameters: Parameters,
 }
 
 impl Context {
@@ -14,19 +27,58 @@ impl Context {
         invoked_function_arn: String,
         identity: String,
         client_context: String,
+        trace_header: Option<TraceHeader>,
+        initialization: Initialization,
+        metrics: Metrics,
     ) -> Context {
         Context {
             aws_request_id,
             invoked_function_arn,
             identity,
             client_context,
+            trace_header,
+            initialization,
+            deadline_ms: None,
+            metrics,
+            parameters: Parameters::default(),
         }
     }
+
+    pub fn deadline(&self) -> Option<SystemTime> {
+        self.deadline_ms
+            .map(|deadline_ms| UNIX_EPOCH + Duration::from_millis(deadline_ms))
+    }
+
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.deadline().map(|deadline| {
+            deadline
+                .duration_since(SystemTime::now())
+                .unwrap_or_default()
+        })
+    }
+}
+
+// the invocation most tests run against, adjusted field by field where a test needs to
+#[cfg(test)]
+pub(crate) async fn test_context() -> Context {
+    Context::create(
+        String::from("8476a536-e9f4-11e8-9739-2dfe598c3fcd"),
+        String::from("arn:aws:lambda:us-east-2:123456789012:function:custom-runtime"),
+        String::from("test_identity"),
+        String::from("test_client_context"),
+        None,
+        Initialization::default(),
+        Metrics::default(),
+    )
+    .await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::initialization::InitializationType;
+    use std::time::Duration;
+
     #[tokio::test]
     async fn context() {
         let test_aws_request_id = String::from("8476a536-e9f4-11e8-9739-2dfe598c3fcd");
@@ -34,12 +86,26 @@ mod tests {
             String::from("arn:aws:lambda:us-east-2:123456789012:function:custom-runtime");
         let test_identity = String::from("test_identity");
         let test_client_context = String::from("test_client_context");
+        let test_trace_header = TraceHeader::parse(
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+        )
+        .await;
+        let test_initialization = Initialization::create(
+            true,
+            InitializationType::OnDemand,
+            Duration::from_millis(100),
+        )
+        .await;
+        let test_metrics = Metrics::create("test_namespace").await;
 
         let test_context = Context::create(
             test_aws_request_id,
             test_arn,
             test_identity,
             test_client_context,
+            test_trace_header,
+            test_initialization,
+            test_metrics,
         )
         .await;
         assert_eq!(
@@ -55,5 +121,41 @@ mod tests {
             test_context.client_context,
             String::from("test_client_context")
         );
+        assert_eq!(
+            test_context.trace_header.as_ref().unwrap().root,
+            String::from("1-5759e988-bd862e3fe1be46a994272793"),
+        );
+        assert!(test_context.initialization.cold_start);
+        assert_eq!(
+            test_context.initialization.initialization_type,
+            InitializationType::OnDemand,
+        );
+        assert_eq!(
+            test_context.initialization.duration,
+            Duration::from_millis(100),
+        );
+        assert_eq!(test_context.deadline(), None);
+        assert_eq!(test_context.remaining_time(), None);
+    }
+
+    #[tokio::test]
+    async fn remaining_time() {
+        let mut test_context = test_context().await;
+
+        test_context.deadline_ms = Some(1542409706888);
+        assert_eq!(
+            test_context.deadline(),
+            Some(UNIX_EPOCH + Duration::from_millis(1542409706888)),
+        );
+        assert_eq!(test_context.remaining_time(), Some(Duration::ZERO));
+
+        let test_deadline = SystemTime::now() + Duration::from_secs(60);
+        test_context.deadline_ms = Some(
+            test_deadline
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_millis() as u64,
+        );
+        assert!(test_context.remaining_time().unwrap() > Duration::from_secs(50));
     }
 }
@@ -0,0 +1,188 @@
+use std::ffi::OsString;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+use tracing::{info, warn};
+
+use crate::core::redaction::REDACTED;
+use crate::core::sigv4;
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+    pub expiration: Option<SystemTime>,
+}
+
+impl Credentials {
+    pub async fn create(
+        access_key_id: &str,
+        secret_access_key: &str,
+        session_token: Option<&str>,
+    ) -> Credentials {
+        Credentials {
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            session_token: session_token.map(str::to_string),
+            expiration: None,
+        }
+    }
+
+    pub async fn from_environment() -> Option<Credentials> {
+        let aws_access_key_id = OsString::from("AWS_ACCESS_KEY_ID");
+        let aws_secret_access_key = OsString::from("AWS_SECRET_ACCESS_KEY");
+        let aws_session_token = OsString::from("AWS_SESSION_TOKEN");
+        let aws_credential_expiration = OsString::from("AWS_CREDENTIAL_EXPIRATION");
+
+        let access_key_id = std::env::var_os(aws_access_key_id)?;
+        let secret_access_key = match std::env::var_os(aws_secret_access_key) {
+            Some(value) => value,
+            None => {
+                warn!(
+                    "| kaon credentials | AWS_ACCESS_KEY_ID is set without AWS_SECRET_ACCESS_KEY"
+                );
+                return None;
+            }
+        };
+
+        let mut credentials = Credentials::create(
+            access_key_id.to_string_lossy().as_ref(),
+            secret_access_key.to_string_lossy().as_ref(),
+            std::env::var_os(aws_session_token)
+                .as_ref()
+                .map(|value| value.to_string_lossy())
+                .as_deref(),
+        )
+        .await;
+
+        if let Some(value) = std::env::var_os(aws_credential_expiration) {
+            match sigv4::parse_timestamp(value.to_string_lossy().as_ref()) {
+                Some(expiration) => credentials.expiration = Some(expiration),
+                None => warn!(
+                    "| kaon credentials | AWS_CREDENTIAL_EXPIRATION is not a valid timestamp - {:?}",
+                    value,
+                ),
+            }
+        }
+
+        info!(
+            "| kaon credentials | loaded credentials for {:?}",
+            &credentials
+        );
+
+        Some(credentials)
+    }
+
+    pub async fn with_expiration(mut self, expiration: SystemTime) -> Credentials {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    pub fn remaining(&self) -> Option<Duration> {
+        let expiration = self.expiration?;
+
+        Some(
+            expiration
+                .duration_since(SystemTime::now())
+                .unwrap_or_default(),
+        )
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_within(Duration::ZERO)
+    }
+
+    // environment credentials carry no expiry and are treated as valid for the sandbox lifetime
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        match self.remaining() {
+            Some(remaining) => remaining <= margin,
+            None => false,
+        }
+    }
+}
+
+// the last four characters are enough to tell keys apart in the logs
+fn mask_access_key_id(access_key_id: &str) -> String {
+    let length = access_key_id.chars().count();
+    let visible = if length > 4 { 4 } else { 0 };
+
+    access_key_id
+        .chars()
+        .enumerate()
+        .map(|(index, character)| {
+            if index < length - visible {
+                '*'
+            } else {
+                character
+            }
+        })
+        .collect()
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &mask_access_key_id(&self.access_key_id))
+            .field("secret_access_key", &REDACTED)
+            .field(
+                "session_token",
+                &self.session_token.as_ref().map(|_| REDACTED),
+            )
+            .field("expiration", &self.expiration.map(sigv4::format_timestamp))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[tokio::test]
+    async fn expiration() {
+        let test_credentials = Credentials::create(
+            "AKIDEXAMPLE",
+            "test_secret_access_key",
+            Some("test_session_token"),
+        )
+        .await;
+        assert!(!test_credentials.is_expired());
+        assert_eq!(test_credentials.remaining(), None);
+
+        let test_credentials = test_credentials
+            .with_expiration(SystemTime::now() + Duration::from_secs(60))
+            .await;
+        assert!(!test_credentials.is_expired());
+        assert!(test_credentials.expires_within(Duration::from_secs(300)));
+        assert!(!test_credentials.expires_within(Duration::from_secs(30)));
+
+        let test_credentials = test_credentials
+            .with_expiration(SystemTime::now() - Duration::from_secs(1))
+            .await;
+        assert!(test_credentials.is_expired());
+        assert_eq!(test_credentials.remaining(), Some(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn debug() {
+        let test_credentials = Credentials::create(
+            "AKIDEXAMPLE",
+            "test_secret_access_key",
+            Some("test_session_token"),
+        )
+        .await
+        .with_expiration(UNIX_EPOCH + Duration::from_secs(1440938160))
+        .await;
+        let test_debug = format!("{:?}", test_credentials);
+
+        assert!(!test_debug.contains("AKIDEXAMPLE"));
+        assert!(test_debug.contains(r#"access_key_id: "*******MPLE""#));
+        assert!(test_debug.contains("20150830T123600Z"));
+        assert!(!test_debug.contains("test_secret_access_key"));
+        assert!(!test_debug.contains("test_session_token"));
+        assert_eq!(test_debug.matches(REDACTED).count(), 2);
+
+        let test_credentials = Credentials::create("AKID", "test_secret_access_key", None).await;
+        assert!(format!("{:?}", test_credentials).contains(r#"access_key_id: "****""#));
+    }
+}
@@ -1,25 +1,176 @@
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::backtrace::{Backtrace, BacktraceStatus};
 
-#[derive(Debug, Deserialize, PartialEq, Serialize)]
+use crate::core::redaction::Redaction;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(from = "String", into = "String")]
 pub enum ErrorType {
     Unhandled,
+    Handled,
+    Panic,
+    UnmarshalError,
+    MarshalError,
+    Timeout,
+    Custom(String),
+}
+
+impl ErrorType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ErrorType::Unhandled => "Unhandled",
+            ErrorType::Handled => "Handled",
+            ErrorType::Panic => "Runtime.Panic",
+            ErrorType::UnmarshalError => "Runtime.UnmarshalError",
+            ErrorType::MarshalError => "Runtime.MarshalError",
+            ErrorType::Timeout => "Runtime.Timeout",
+            ErrorType::Custom(error_type) => error_type.as_str(),
+        }
+    }
+}
+
+impl From<String> for ErrorType {
+    fn from(error_type: String) -> ErrorType {
+        match error_type.as_str() {
+            "Unhandled" => ErrorType::Unhandled,
+            "Handled" => ErrorType::Handled,
+            "Runtime.Panic" => ErrorType::Panic,
+            "Runtime.UnmarshalError" => ErrorType::UnmarshalError,
+            "Runtime.MarshalError" => ErrorType::MarshalError,
+            "Runtime.Timeout" => ErrorType::Timeout,
+            _ => ErrorType::Custom(error_type),
+        }
+    }
+}
+
+impl From<ErrorType> for String {
+    fn from(error_type: ErrorType) -> String {
+        error_type.as_str().to_string()
+    }
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ErrorRequest {
     error_message: String,
     error_type: ErrorType,
-    stack_trace: String,
+    stack_trace: Vec<String>,
 }
 
 impl ErrorRequest {
     pub async fn collect(error_message: String) -> ErrorRequest {
+        ErrorRequest::create(ErrorType::Unhandled, error_message).await
+    }
+
+    // captured where the error is created, unhandled errors always carry their frames while
+    // handled and custom ones only do when RUST_BACKTRACE asks, keeping symbolication off the
+    // expected error path
+    pub async fn create(error_type: ErrorType, error_message: String) -> ErrorRequest {
+        let backtrace = match error_type {
+            ErrorType::Unhandled | ErrorType::Panic => Backtrace::force_capture(),
+            _ => Backtrace::capture(),
+        };
+        let stack_trace = match backtrace.status() {
+            BacktraceStatus::Captured => stack_trace(&backtrace.to_string()),
+            _ => Vec::new(),
+        };
+
+        ErrorRequest {
+            error_message,
+            error_type,
+            stack_trace,
+        }
+    }
+
+    // errors kaon raises itself, where a trace would only show the runtime reporting them
+    pub(crate) async fn runtime(error_type: ErrorType, error_message: String) -> ErrorRequest {
         ErrorRequest {
             error_message,
-            error_type: ErrorType::Unhandled,
-            stack_trace: String::from("unused"),
+            error_type,
+            stack_trace: Vec::new(),
+        }
+    }
+
+    pub async fn panic(error_message: String, backtrace: String) -> ErrorRequest {
+        ErrorRequest {
+            error_message,
+            error_type: ErrorType::Panic,
+            stack_trace: stack_trace(&backtrace),
+        }
+    }
+
+    pub fn error_type(&self) -> &ErrorType {
+        &self.error_type
+    }
+
+    pub fn error_message(&self) -> &str {
+        &self.error_message
+    }
+
+    pub async fn redact(&mut self, redaction: &Redaction) {
+        self.error_message = redaction.redact_text(&self.error_message);
+    }
+
+    pub fn xray_error_cause(&self, working_directory: &str) -> String {
+        let mut paths: Vec<&str> = Vec::with_capacity(self.stack_trace.len());
+        let mut stack = Vec::with_capacity(self.stack_trace.len());
+
+        for frame in self.stack_trace.iter() {
+            let frame = frame
+                .split_once(": ")
+                .map(|(_, frame)| frame)
+                .unwrap_or(frame);
+
+            match frame.rsplit_once(" at ") {
+                Some((label, location)) => {
+                    let mut location_parts = location.rsplitn(3, ':');
+                    let _column = location_parts.next();
+                    let line = location_parts
+                        .next()
+                        .and_then(|line| line.parse::<u32>().ok());
+                    let path = location_parts.next().unwrap_or(location);
+
+                    if !paths.contains(&path) {
+                        paths.push(path);
+                    }
+
+                    stack.push(json!({ "path": path, "line": line, "label": label }));
+                }
+                None => stack.push(json!({ "label": frame })),
+            }
+        }
+
+        json!({
+            "working_directory": working_directory,
+            "exceptions": [{
+                "type": self.error_type.as_str(),
+                "message": self.error_message,
+                "stack": stack,
+            }],
+            "paths": paths,
+        })
+        .to_string()
+    }
+}
+
+fn stack_trace(backtrace: &str) -> Vec<String> {
+    let mut frames: Vec<String> = Vec::with_capacity(16);
+
+    for line in backtrace.lines().map(str::trim) {
+        match line.strip_prefix("at ") {
+            Some(location) => {
+                if let Some(frame) = frames.last_mut() {
+                    frame.push_str(" at ");
+                    frame.push_str(location);
+                }
+            }
+            None if line.is_empty() || line.ends_with(" backtrace") => continue,
+            None => frames.push(line.to_string()),
         }
     }
+
+    frames
 }
 
 #[cfg(test)]
@@ -34,6 +185,118 @@ mod tests {
             String::from("some test error"),
         );
         assert_eq!(test_error_request.error_type, ErrorType::Unhandled);
-        assert_eq!(test_error_request.stack_trace, String::from("unused"));
+        assert!(test_error_request
+            .stack_trace
+            .iter()
+            .all(|frame| !frame.is_empty()));
+    }
+
+    #[tokio::test]
+    async fn create() {
+        let test_error_request =
+            ErrorRequest::create(ErrorType::Unhandled, String::from("some test error")).await;
+        assert_eq!(test_error_request.error_message(), "some test error");
+        assert!(test_error_request
+            .stack_trace
+            .iter()
+            .any(|frame| frame.contains("error::tests::create")));
+
+        let test_custom = ErrorRequest::create(
+            ErrorType::Custom(String::from("Test.ValidationError")),
+            String::from("some test error"),
+        )
+        .await;
+        assert_eq!(test_custom.error_type().as_str(), "Test.ValidationError");
+        assert_eq!(test_custom.error_message(), "some test error");
+        assert!(test_custom
+            .stack_trace
+            .iter()
+            .all(|frame| !frame.is_empty()));
+
+        let test_unmarshal =
+            ErrorRequest::create(ErrorType::UnmarshalError, String::from("some test error")).await;
+        assert_eq!(
+            serde_json::to_value(&test_unmarshal).unwrap()["errorType"],
+            "Runtime.UnmarshalError",
+        );
+    }
+
+    #[tokio::test]
+    async fn error_type() {
+        for test_error_type in [
+            ErrorType::Unhandled,
+            ErrorType::Handled,
+            ErrorType::Panic,
+            ErrorType::UnmarshalError,
+            ErrorType::MarshalError,
+            ErrorType::Timeout,
+            ErrorType::Custom(String::from("Test.Error")),
+        ] {
+            let test_serialized = serde_json::to_string(&test_error_type).unwrap();
+            let test_deserialized: ErrorType = serde_json::from_str(&test_serialized).unwrap();
+            assert_eq!(test_deserialized, test_error_type);
+        }
+
+        assert_eq!(ErrorType::MarshalError.as_str(), "Runtime.MarshalError");
+    }
+
+    #[tokio::test]
+    async fn panic() {
+        let test_panic = String::from("some test panic");
+        let test_backtrace = String::from(
+            "   0: kaon::core::handler::tests\n             at ./src/core/handler.rs:10:5\n   1: core::ops::function::FnOnce::call_once",
+        );
+        let test_error_request = ErrorRequest::panic(test_panic, test_backtrace).await;
+        assert_eq!(
+            test_error_request.error_message,
+            String::from("some test panic"),
+        );
+        assert_eq!(test_error_request.error_type, ErrorType::Panic);
+        assert_eq!(
+            test_error_request.stack_trace,
+            vec![
+                String::from("0: kaon::core::handler::tests at ./src/core/handler.rs:10:5"),
+                String::from("1: core::ops::function::FnOnce::call_once"),
+            ],
+        );
+        assert_eq!(
+            serde_json::to_value(&test_error_request).unwrap()["errorType"],
+            "Runtime.Panic",
+        );
+    }
+
+    async fn test_golden_error_request() -> ErrorRequest {
+        let test_backtrace = String::from(
+            "   0: kaon::core::handler::tests::run_panicked::test_handler_function
+             at ./src/core/handler.rs:186:17
+   1: kaon::core::handler::EventHandler<EventFunction>::run
+             at ./src/core/handler.rs:97:72
+   2: __rust_try",
+        );
+
+        ErrorRequest::panic(String::from("test_panic_message"), test_backtrace).await
+    }
+
+    #[tokio::test]
+    async fn golden_error_request() {
+        let test_error_request = test_golden_error_request().await;
+        let test_payload = serde_json::to_value(&test_error_request).unwrap();
+        let test_golden: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/golden/error_request.json")).unwrap();
+        assert_eq!(test_payload, test_golden);
+
+        let test_deserialized: ErrorRequest = serde_json::from_value(test_golden).unwrap();
+        assert_eq!(test_deserialized.error_type, ErrorType::Panic);
+        assert_eq!(test_deserialized.stack_trace.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn golden_xray_error_cause() {
+        let test_error_request = test_golden_error_request().await;
+        let test_cause: serde_json::Value =
+            serde_json::from_str(&test_error_request.xray_error_cause("/var/task")).unwrap();
+        let test_golden: serde_json::Value =
+            serde_json::from_str(include_str!("../../tests/golden/xray_error_cause.json")).unwrap();
+        assert_eq!(test_cause, test_golden);
     }
 }
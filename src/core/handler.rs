@@ -1,5 +1,101 @@
+use crate::core::error::ErrorRequest;
 use crate::core::Context;
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
 use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::Once;
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
+use tracing::error;
+
+static PANIC_HOOK: Once = Once::new();
+
+thread_local! {
+    static PANIC_BACKTRACE: RefCell<Option<String>> = const { RefCell::new(None) };
+    static IN_HANDLER: Cell<bool> = const { Cell::new(false) };
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Panicked {
+    pub message: String,
+    pub backtrace: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum HandlerError {
+    Failed,
+    Handled(ErrorRequest),
+    Unmarshal(String),
+    Marshal(String),
+    InvalidRequest(String),
+    InvalidResponse(String),
+    Panicked(Panicked),
+    TimedOut(Duration),
+}
+
+impl From<()> for HandlerError {
+    fn from(_: ()) -> HandlerError {
+        HandlerError::Failed
+    }
+}
+
+impl From<ErrorRequest> for HandlerError {
+    fn from(error_request: ErrorRequest) -> HandlerError {
+        HandlerError::Handled(error_request)
+    }
+}
+
+struct CatchUnwind<Handling: Future> {
+    handling: Pin<Box<Handling>>,
+}
+
+impl<Handling: Future> Future for CatchUnwind<Handling> {
+    type Output = Result<Handling::Output, Box<dyn Any + Send>>;
+
+    fn poll(self: Pin<&mut Self>, task_context: &mut TaskContext<'_>) -> Poll<Self::Output> {
+        let handling = &mut self.get_mut().handling;
+
+        let entered = IN_HANDLER.with(|in_handler| in_handler.replace(true));
+        let polled = catch_unwind(AssertUnwindSafe(|| handling.as_mut().poll(task_context)));
+        IN_HANDLER.with(|in_handler| in_handler.set(entered));
+
+        match polled {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(panic) => Poll::Ready(Err(panic)),
+        }
+    }
+}
+
+fn capture_panic_backtraces() {
+    PANIC_HOOK.call_once(|| {
+        let previous_hook = std::panic::take_hook();
+
+        // handler panics are reported through the redacted error request, the previous hook
+        // would print the raw message and backtrace, so it only sees panics outside handlers
+        std::panic::set_hook(Box::new(move |panic_info| {
+            if IN_HANDLER.with(Cell::get) {
+                let backtrace = Backtrace::force_capture().to_string();
+                PANIC_BACKTRACE.with(|captured| *captured.borrow_mut() = Some(backtrace));
+            } else {
+                previous_hook(panic_info);
+            }
+        }));
+    });
+}
+
+fn panic_message(panic: &(dyn Any + Send)) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.to_owned()
+    } else {
+        String::from("handler panicked")
+    }
+}
 
 #[derive(Debug)]
 pub struct EventHandler<EventFunction> {
@@ -7,34 +103,46 @@ pub struct EventHandler<EventFunction> {
 }
 
 impl<EventFunction> EventHandler<EventFunction> {
-    pub async fn init<EventRequest, EventResponse, Outatime>(
+    pub async fn init<EventRequest, EventResponse, EventError, Outatime>(
         function: EventFunction,
     ) -> EventHandler<EventFunction>
     where
         EventFunction: Fn(EventRequest, Context) -> Outatime,
-        Outatime: Future<Output = Result<EventResponse, ()>>,
+        EventError: Into<HandlerError>,
+        Outatime: Future<Output = Result<EventResponse, EventError>>,
     {
+        capture_panic_backtraces();
+
         EventHandler { function }
     }
 
-    pub async fn run<EventRequest, EventResponse, Outatime>(
+    pub async fn run<EventRequest, EventResponse, EventError, Outatime>(
         &self,
         event: EventRequest,
         context: Context,
-    ) -> Result<EventResponse, ()>
+    ) -> Result<EventResponse, HandlerError>
     where
         EventFunction: Fn(EventRequest, Context) -> Outatime,
-        Outatime: Future<Output = Result<EventResponse, ()>>,
+        EventError: Into<HandlerError>,
+        Outatime: Future<Output = Result<EventResponse, EventError>>,
     {
-        let event_result = (self.function)(event, context).await;
+        let handling = CatchUnwind {
+            handling: Box::pin(async { (self.function)(event, context).await }),
+        };
 
-        match event_result {
-            Ok(result) => Ok(result),
-            Err(error) => {
-                let json_error = serde_json::to_string(&error).unwrap();
+        match handling.await {
+            Ok(Ok(result)) => Ok(result),
+            // logged by kaon once the message is redacted
+            Ok(Err(error)) => Err(error.into()),
+            Err(panic) => {
+                let message = panic_message(panic.as_ref());
+                let backtrace = PANIC_BACKTRACE
+                    .with(|captured| captured.borrow_mut().take())
+                    .unwrap_or_default();
 
-                println!("error encountered - {:?}", json_error);
-                Err(())
+                // the message is reported through the redacted error request instead
+                error!("| kaon handler | handler panicked");
+                Err(HandlerError::Panicked(Panicked { message, backtrace }))
             }
         }
     }
@@ -43,6 +151,9 @@ impl<EventFunction> EventHandler<EventFunction> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::context::test_context;
+    use crate::core::initialization::Initialization;
+    use crate::core::metrics::Metrics;
     #[tokio::test]
     async fn init() {
         struct TestRequest {
@@ -63,12 +174,17 @@ mod tests {
             String::from("arn:aws:lambda:us-east-2:123456789012:function:custom-runtime");
         let test_identity = String::from("test_identity");
         let test_client_context = String::from("test_client_context");
+        let test_initialization = Initialization::default();
+        let test_metrics = Metrics::default();
 
         let test_context = Context::create(
             test_aws_request_id,
             test_arn,
             test_identity,
             test_client_context,
+            None,
+            test_initialization,
+            test_metrics,
         )
         .await;
 
@@ -105,4 +221,35 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn run_panicked() {
+        async fn test_handler_function(event: String, _context: Context) -> Result<String, ()> {
+            if event == "test_panic" {
+                panic!("test_panic_message");
+            }
+            Ok(event)
+        }
+
+        let test_context = test_context().await;
+
+        let event_handler = EventHandler::init(test_handler_function).await;
+        let test_result = event_handler
+            .run(String::from("test_panic"), test_context.clone())
+            .await;
+
+        match test_result {
+            Err(HandlerError::Panicked(panicked)) => {
+                assert_eq!(panicked.message, String::from("test_panic_message"));
+                assert!(!panicked.backtrace.is_empty());
+            }
+            _ => panic!("expected the handler panic to be captured"),
+        }
+        assert!(!IN_HANDLER.with(Cell::get));
+
+        let test_result = event_handler
+            .run(String::from("test_recovered"), test_context)
+            .await;
+        assert_eq!(test_result, Ok(String::from("test_recovered")));
+    }
 }
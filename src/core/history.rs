@@ -0,0 +1,156 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::core::context::Context;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum Outcome {
+    Success,
+    Error(String),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct InvocationRecord {
+    pub context: Context,
+    pub outcome: Outcome,
+    pub duration: Duration,
+    pub response_size: usize,
+}
+
+impl InvocationRecord {
+    pub async fn create(
+        context: Context,
+        result: &Result<usize, String>,
+        duration: Duration,
+    ) -> InvocationRecord {
+        let (outcome, response_size) = match result {
+            Ok(response_size) => (Outcome::Success, *response_size),
+            Err(error) => (Outcome::Error(error.to_owned()), 0),
+        };
+
+        InvocationRecord {
+            context,
+            outcome,
+            duration,
+            response_size,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Records {
+    capacity: usize,
+    records: VecDeque<InvocationRecord>,
+}
+
+#[derive(Clone, Debug)]
+pub struct History {
+    records: Arc<Mutex<Records>>,
+}
+
+impl History {
+    pub async fn create(capacity: usize) -> History {
+        let capacity = capacity.max(1);
+
+        History {
+            records: Arc::new(Mutex::new(Records {
+                capacity,
+                records: VecDeque::with_capacity(capacity),
+            })),
+        }
+    }
+
+    fn records(&self) -> MutexGuard<'_, Records> {
+        match self.records.lock() {
+            Ok(records) => records,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    pub async fn record(&self, record: InvocationRecord) {
+        let mut records = self.records();
+
+        if records.records.len() == records.capacity {
+            records.records.pop_front();
+        }
+
+        records.records.push_back(record);
+    }
+
+    pub async fn capacity(&self) -> usize {
+        self.records().capacity
+    }
+
+    pub async fn len(&self) -> usize {
+        self.records().records.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.records().records.is_empty()
+    }
+
+    pub async fn latest(&self) -> Option<InvocationRecord> {
+        self.records().records.back().cloned()
+    }
+
+    pub async fn errors(&self) -> usize {
+        self.records()
+            .records
+            .iter()
+            .filter(|record| record.outcome != Outcome::Success)
+            .count()
+    }
+
+    pub async fn snapshot(&self) -> Vec<InvocationRecord> {
+        self.records().records.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_context;
+
+    async fn test_record(aws_request_id: &str, result: Result<usize, String>) -> InvocationRecord {
+        let mut test_context = test_context().await;
+        test_context.aws_request_id = aws_request_id.to_string();
+
+        InvocationRecord::create(test_context, &result, Duration::from_millis(3)).await
+    }
+
+    #[tokio::test]
+    async fn record() {
+        let test_history = History::create(2).await;
+        assert!(test_history.is_empty().await);
+        assert_eq!(test_history.capacity().await, 2);
+
+        test_history
+            .record(test_record("test_first", Ok(25)).await)
+            .await;
+        test_history
+            .record(test_record("test_second", Err(String::from("test_error"))).await)
+            .await;
+        test_history
+            .record(test_record("test_third", Ok(10)).await)
+            .await;
+
+        assert_eq!(test_history.len().await, 2);
+        assert_eq!(test_history.errors().await, 1);
+
+        let test_snapshot = test_history.snapshot().await;
+        assert_eq!(test_snapshot[0].context.aws_request_id, "test_second");
+        assert_eq!(
+            test_snapshot[0].outcome,
+            Outcome::Error(String::from("test_error")),
+        );
+        assert_eq!(test_snapshot[0].response_size, 0);
+
+        let test_latest = test_history.latest().await.unwrap();
+        assert_eq!(test_latest.context.aws_request_id, "test_third");
+        assert_eq!(test_latest.outcome, Outcome::Success);
+        assert_eq!(test_latest.response_size, 10);
+        assert_eq!(test_latest.duration, Duration::from_millis(3));
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::core::context::Context;
+
+pub type HookFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+struct Hook {
+    name: String,
+    timeout: Duration,
+    function: Box<dyn Fn(&Context) -> HookFuture + Send + Sync>,
+}
+
+#[derive(Default)]
+pub struct Hooks {
+    hooks: Vec<Hook>,
+}
+
+impl Hooks {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+    pub async fn register<HookFunction, Flush>(
+        &mut self,
+        name: &str,
+        timeout: Duration,
+        function: HookFunction,
+    ) where
+        HookFunction: Fn(&Context) -> Flush + Send + Sync + 'static,
+        Flush: Future<Output = ()> + Send + 'static,
+    {
+        info!(
+            "| kaon hooks | registered {} with timeout {:?}",
+            name, timeout
+        );
+
+        self.hooks.push(Hook {
+            name: name.to_string(),
+            timeout,
+            function: Box::new(move |context| Box::pin(function(context))),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub async fn run(&self, context: &Context) -> usize {
+        let mut timed_out = 0;
+
+        for hook in self.hooks.iter() {
+            // hooks borrow the context and copy only what their flush needs
+            let flush = (hook.function)(context);
+
+            match tokio::time::timeout(hook.timeout, flush).await {
+                Ok(()) => info!("| kaon hooks | {} completed", hook.name),
+                Err(_) => {
+                    warn!(
+                        "| kaon hooks | {} did not complete within {:?}",
+                        hook.name, hook.timeout,
+                    );
+                    timed_out += 1;
+                }
+            }
+        }
+
+        timed_out
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.hooks.iter().map(|hook| (&hook.name, hook.timeout)))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_context;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test(start_paused = true)]
+    async fn run() {
+        let test_context = test_context().await;
+
+        let test_flushed = Arc::new(AtomicUsize::new(0));
+        let test_hook_flushed = test_flushed.clone();
+        let mut test_hooks = Hooks::default();
+        assert!(test_hooks.is_empty());
+
+        test_hooks
+            .register("test_flush", Hooks::DEFAULT_TIMEOUT, move |context| {
+                let flushed = test_hook_flushed.clone();
+                let aws_request_id = context.aws_request_id.clone();
+                async move {
+                    assert_eq!(
+                        aws_request_id,
+                        String::from("8476a536-e9f4-11e8-9739-2dfe598c3fcd"),
+                    );
+                    flushed.fetch_add(1, Ordering::SeqCst);
+                }
+            })
+            .await;
+        test_hooks
+            .register("test_slow_flush", Duration::from_millis(10), |_| async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+            })
+            .await;
+        assert_eq!(test_hooks.len(), 2);
+
+        let test_timed_out = test_hooks.run(&test_context).await;
+        assert_eq!(test_timed_out, 1);
+        assert_eq!(test_flushed.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,564 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info, warn};
+
+use crate::core::context::Context;
+
+pub type StoreFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum IdempotencyStatus {
+    InProgress,
+    Completed,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct IdempotencyRecord {
+    pub status: IdempotencyStatus,
+    pub response: Option<String>,
+    pub expires_at: u64,
+}
+
+impl IdempotencyRecord {
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at <= now
+    }
+}
+
+pub trait IdempotencyStore: fmt::Debug + Send + Sync {
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<IdempotencyRecord>>;
+
+    // stores `record` unless an unexpired record already exists for `key`, which is returned instead
+    fn lock<'a>(
+        &'a self,
+        key: &'a str,
+        record: IdempotencyRecord,
+        now: u64,
+    ) -> StoreFuture<'a, Result<(), IdempotencyRecord>>;
+
+    fn put<'a>(&'a self, key: &'a str, record: IdempotencyRecord) -> StoreFuture<'a, ()>;
+
+    fn remove<'a>(&'a self, key: &'a str) -> StoreFuture<'a, ()>;
+}
+
+#[derive(Debug, Default)]
+struct LruRecords {
+    records: HashMap<String, IdempotencyRecord>,
+    order: VecDeque<String>,
+}
+
+impl LruRecords {
+    fn touch(&mut self, key: &str) {
+        if let Some(position) = self.order.iter().position(|recent| recent == key) {
+            if let Some(recent) = self.order.remove(position) {
+                self.order.push_back(recent);
+            }
+        }
+    }
+
+    fn insert(&mut self, key: &str, record: IdempotencyRecord, capacity: usize) {
+        if self.records.insert(key.to_string(), record).is_some() {
+            self.touch(key);
+            return;
+        }
+
+        self.order.push_back(key.to_string());
+
+        while self.order.len() > capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.records.remove(&evicted);
+            }
+        }
+    }
+
+    fn remove(&mut self, key: &str) {
+        if self.records.remove(key).is_some() {
+            self.order.retain(|recent| recent != key);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct InMemoryStore {
+    capacity: usize,
+    records: Mutex<LruRecords>,
+}
+
+impl InMemoryStore {
+    pub async fn create(capacity: usize) -> InMemoryStore {
+        InMemoryStore {
+            capacity: capacity.max(1),
+            records: Mutex::new(LruRecords::default()),
+        }
+    }
+
+    fn records(&self) -> std::sync::MutexGuard<'_, LruRecords> {
+        match self.records.lock() {
+            Ok(records) => records,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl IdempotencyStore for InMemoryStore {
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<IdempotencyRecord>> {
+        Box::pin(async move {
+            let mut records = self.records();
+            records.touch(key);
+            records.records.get(key).cloned()
+        })
+    }
+
+    fn lock<'a>(
+        &'a self,
+        key: &'a str,
+        record: IdempotencyRecord,
+        now: u64,
+    ) -> StoreFuture<'a, Result<(), IdempotencyRecord>> {
+        Box::pin(async move {
+            let mut records = self.records();
+
+            match records.records.get(key) {
+                Some(existing) if !existing.is_expired(now) => {
+                    let existing = existing.clone();
+                    records.touch(key);
+                    Err(existing)
+                }
+                _ => {
+                    records.insert(key, record, self.capacity);
+                    Ok(())
+                }
+            }
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, record: IdempotencyRecord) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.records().insert(key, record, self.capacity);
+        })
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.records().remove(key);
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct FileStore {
+    path: PathBuf,
+    guard: Mutex<()>,
+}
+
+impl FileStore {
+    pub async fn create(path: impl Into<PathBuf>) -> FileStore {
+        FileStore {
+            path: path.into(),
+            guard: Mutex::new(()),
+        }
+    }
+
+    fn read(&self) -> HashMap<String, IdempotencyRecord> {
+        match std::fs::read(&self.path) {
+            Ok(contents) => serde_json::from_slice(&contents).unwrap_or_else(|error| {
+                error!(
+                    "| kaon idempotency | {:?} is not readable - {}",
+                    self.path, error
+                );
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    fn write(&self, records: &HashMap<String, IdempotencyRecord>) {
+        let contents = serde_json::to_vec(records).unwrap();
+
+        if let Err(error) = std::fs::write(&self.path, contents) {
+            error!(
+                "| kaon idempotency | {:?} is not writable - {}",
+                self.path, error
+            );
+        }
+    }
+
+    fn update<Update, Output>(&self, update: Update) -> Output
+    where
+        Update: FnOnce(&mut HashMap<String, IdempotencyRecord>) -> (Output, bool),
+    {
+        let _guard = match self.guard.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let mut records = self.read();
+        let (output, changed) = update(&mut records);
+
+        if changed {
+            self.write(&records);
+        }
+
+        output
+    }
+}
+
+impl IdempotencyStore for FileStore {
+    fn get<'a>(&'a self, key: &'a str) -> StoreFuture<'a, Option<IdempotencyRecord>> {
+        Box::pin(async move { self.update(|records| (records.get(key).cloned(), false)) })
+    }
+
+    fn lock<'a>(
+        &'a self,
+        key: &'a str,
+        record: IdempotencyRecord,
+        now: u64,
+    ) -> StoreFuture<'a, Result<(), IdempotencyRecord>> {
+        Box::pin(async move {
+            self.update(|records| match records.get(key) {
+                Some(existing) if !existing.is_expired(now) => (Err(existing.clone()), false),
+                _ => {
+                    records.insert(key.to_string(), record);
+                    (Ok(()), true)
+                }
+            })
+        })
+    }
+
+    fn put<'a>(&'a self, key: &'a str, record: IdempotencyRecord) -> StoreFuture<'a, ()> {
+        Box::pin(async move {
+            self.update(|records| {
+                records.insert(key.to_string(), record);
+                ((), true)
+            })
+        })
+    }
+
+    fn remove<'a>(&'a self, key: &'a str) -> StoreFuture<'a, ()> {
+        Box::pin(async move { self.update(|records| ((), records.remove(key).is_some())) })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdempotencyKey {
+    RequestId,
+    Payload(Vec<String>),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Acquired {
+    Lock,
+    Completed(String),
+    InProgress,
+}
+
+#[derive(Debug)]
+pub struct Idempotency {
+    pub store: Box<dyn IdempotencyStore>,
+    pub key: IdempotencyKey,
+    pub ttl: Duration,
+    // None holds the lock until the invocation deadline plus LOCK_MARGIN
+    pub lock_ttl: Option<Duration>,
+}
+
+impl Idempotency {
+    // covers posting the response after the deadline without blocking retries of an invocation
+    // whose sandbox was killed for longer than needed
+    pub const LOCK_MARGIN: Duration = Duration::from_secs(5);
+    // the longest an invocation can run, used when the runtime api sends no deadline
+    pub const LOCK_TTL: Duration = Duration::from_secs(900);
+
+    pub async fn create(
+        store: impl IdempotencyStore + 'static,
+        key: IdempotencyKey,
+        ttl: Duration,
+    ) -> Idempotency {
+        Idempotency {
+            store: Box::new(store),
+            key,
+            ttl,
+            lock_ttl: None,
+        }
+    }
+
+    pub async fn lock_ttl(&self, context: &Context) -> Duration {
+        match (self.lock_ttl, context.remaining_time()) {
+            (Some(lock_ttl), _) => lock_ttl,
+            (None, Some(remaining)) => remaining + Idempotency::LOCK_MARGIN,
+            (None, None) => Idempotency::LOCK_TTL,
+        }
+    }
+
+    pub async fn key(&self, context: &Context, payload: &[u8]) -> Option<String> {
+        match &self.key {
+            IdempotencyKey::RequestId => Some(context.aws_request_id.to_owned()),
+            IdempotencyKey::Payload(pointers) => {
+                let payload: Value = serde_json::from_slice(payload).ok()?;
+                let mut values = Vec::with_capacity(pointers.len());
+
+                for pointer in pointers.iter() {
+                    match payload.pointer(pointer) {
+                        Some(Value::String(value)) => values.push(value.to_owned()),
+                        Some(Value::Null) | None => {
+                            warn!("| kaon idempotency | {} not found in payload", pointer);
+                            return None;
+                        }
+                        Some(value) => values.push(value.to_string()),
+                    }
+                }
+
+                Some(values.join("#"))
+            }
+        }
+    }
+
+    pub async fn acquire(&self, key: &str, context: &Context) -> Acquired {
+        let now = now();
+        let lock = IdempotencyRecord {
+            status: IdempotencyStatus::InProgress,
+            response: None,
+            expires_at: now + self.lock_ttl(context).await.as_millis() as u64,
+        };
+
+        match self.store.lock(key, lock, now).await {
+            Ok(()) => Acquired::Lock,
+            Err(existing) => match (existing.status, existing.response) {
+                (IdempotencyStatus::Completed, Some(response)) => {
+                    info!(
+                        "| kaon idempotency | {} already completed",
+                        fingerprint(key)
+                    );
+                    Acquired::Completed(response)
+                }
+                _ => {
+                    warn!(
+                        "| kaon idempotency | {} is already in progress",
+                        fingerprint(key),
+                    );
+                    Acquired::InProgress
+                }
+            },
+        }
+    }
+
+    pub async fn complete(&self, key: &str, response: &[u8]) {
+        let record = IdempotencyRecord {
+            status: IdempotencyStatus::Completed,
+            response: Some(String::from_utf8_lossy(response).into_owned()),
+            expires_at: now() + self.ttl.as_millis() as u64,
+        };
+
+        self.store.put(key, record).await;
+    }
+
+    pub async fn release(&self, key: &str) {
+        self.store.remove(key).await;
+    }
+}
+
+// keys can be built from payload fields, so logs and errors only carry a digest of them
+pub(crate) fn fingerprint(key: &str) -> String {
+    hex::encode(&Sha256::digest(key.as_bytes())[..8])
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_context;
+
+    #[tokio::test]
+    async fn key() {
+        let test_context = test_context().await;
+        let test_payload = br#"{"order": {"id": 42, "customer": "test_customer"}}"#;
+
+        let test_request_id = Idempotency::create(
+            InMemoryStore::create(1).await,
+            IdempotencyKey::RequestId,
+            Duration::from_secs(60),
+        )
+        .await;
+        assert_eq!(
+            test_request_id.key(&test_context, test_payload).await,
+            Some(String::from("8476a536-e9f4-11e8-9739-2dfe598c3fcd")),
+        );
+
+        let test_payload_key = Idempotency::create(
+            InMemoryStore::create(1).await,
+            IdempotencyKey::Payload(vec![
+                String::from("/order/id"),
+                String::from("/order/customer"),
+            ]),
+            Duration::from_secs(60),
+        )
+        .await;
+        assert_eq!(
+            test_payload_key.key(&test_context, test_payload).await,
+            Some(String::from("42#test_customer")),
+        );
+        assert_eq!(
+            test_payload_key
+                .key(&test_context, br#"{"order": {"id": 42}}"#)
+                .await,
+            None,
+        );
+    }
+
+    #[tokio::test]
+    async fn in_memory_store() {
+        let test_context = test_context().await;
+        let test_idempotency = Idempotency::create(
+            InMemoryStore::create(2).await,
+            IdempotencyKey::RequestId,
+            Duration::from_secs(60),
+        )
+        .await;
+
+        assert_eq!(
+            test_idempotency.acquire("test_key", &test_context).await,
+            Acquired::Lock
+        );
+        assert_eq!(
+            test_idempotency.acquire("test_key", &test_context).await,
+            Acquired::InProgress,
+        );
+
+        test_idempotency
+            .complete("test_key", br#"{"test_response":"hello"}"#)
+            .await;
+        assert_eq!(
+            test_idempotency.acquire("test_key", &test_context).await,
+            Acquired::Completed(String::from(r#"{"test_response":"hello"}"#)),
+        );
+
+        assert_eq!(
+            test_idempotency.acquire("test_failed", &test_context).await,
+            Acquired::Lock
+        );
+        test_idempotency.release("test_failed").await;
+        assert_eq!(
+            test_idempotency.acquire("test_failed", &test_context).await,
+            Acquired::Lock
+        );
+
+        assert_eq!(
+            test_idempotency
+                .acquire("test_evicting", &test_context)
+                .await,
+            Acquired::Lock
+        );
+        assert!(test_idempotency.store.get("test_key").await.is_none());
+        assert!(test_idempotency.store.get("test_evicting").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn expired() {
+        let test_context = test_context().await;
+        let test_store = InMemoryStore::create(2).await;
+        let test_expired = IdempotencyRecord {
+            status: IdempotencyStatus::Completed,
+            response: Some(String::from("test_response")),
+            expires_at: 1,
+        };
+        test_store.put("test_key", test_expired).await;
+
+        let test_idempotency =
+            Idempotency::create(test_store, IdempotencyKey::RequestId, Duration::ZERO).await;
+        assert_eq!(
+            test_idempotency.acquire("test_key", &test_context).await,
+            Acquired::Lock
+        );
+    }
+
+    #[tokio::test]
+    async fn file_store() {
+        let test_context = test_context().await;
+        let test_path = std::env::temp_dir().join(format!(
+            "kaon_idempotency_{}_{}.json",
+            std::process::id(),
+            now(),
+        ));
+        let test_idempotency = Idempotency::create(
+            FileStore::create(&test_path).await,
+            IdempotencyKey::RequestId,
+            Duration::from_secs(60),
+        )
+        .await;
+
+        assert_eq!(
+            test_idempotency.acquire("test_key", &test_context).await,
+            Acquired::Lock
+        );
+        test_idempotency
+            .complete("test_key", b"test_response")
+            .await;
+
+        let test_reopened = FileStore::create(&test_path).await;
+        let test_record = test_reopened.get("test_key").await.unwrap();
+        assert_eq!(test_record.status, IdempotencyStatus::Completed);
+        assert_eq!(test_record.response, Some(String::from("test_response")));
+
+        test_reopened.remove("test_key").await;
+        assert!(test_idempotency.store.get("test_key").await.is_none());
+
+        std::fs::remove_file(test_path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn lock_ttl() {
+        let mut test_context = test_context().await;
+        let mut test_idempotency = Idempotency::create(
+            InMemoryStore::create(2).await,
+            IdempotencyKey::RequestId,
+            Duration::from_secs(60),
+        )
+        .await;
+        assert_eq!(
+            test_idempotency.lock_ttl(&test_context).await,
+            Idempotency::LOCK_TTL,
+        );
+
+        test_context.deadline_ms = Some(now() + 3000);
+        let test_lock_ttl = test_idempotency.lock_ttl(&test_context).await;
+        assert!(test_lock_ttl > Idempotency::LOCK_MARGIN);
+        assert!(test_lock_ttl <= Idempotency::LOCK_MARGIN + Duration::from_secs(3));
+
+        // a lock left behind by a killed sandbox stops blocking retries once it expires
+        test_idempotency.lock_ttl = Some(Duration::ZERO);
+        assert_eq!(
+            test_idempotency.lock_ttl(&test_context).await,
+            Duration::ZERO
+        );
+        assert_eq!(
+            test_idempotency.acquire("test_key", &test_context).await,
+            Acquired::Lock,
+        );
+        assert_eq!(
+            test_idempotency.acquire("test_key", &test_context).await,
+            Acquired::Lock,
+        );
+    }
+
+    #[tokio::test]
+    async fn fingerprint() {
+        let test_fingerprint = super::fingerprint("42#test_customer");
+
+        assert_eq!(test_fingerprint.len(), 16);
+        assert!(!test_fingerprint.contains("test_customer"));
+        assert_eq!(test_fingerprint, super::fingerprint("42#test_customer"));
+        assert_ne!(test_fingerprint, super::fingerprint("43#test_customer"));
+    }
+}
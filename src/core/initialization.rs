@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::time::Duration;
+use tracing::{info, instrument};
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum InitializationType {
+    OnDemand,
+    ProvisionedConcurrency,
+    SnapStart,
+    Unknown(String),
+}
+
+impl InitializationType {
+    pub async fn parse(value: &str) -> InitializationType {
+        match value {
+            "on-demand" => InitializationType::OnDemand,
+            "provisioned-concurrency" => InitializationType::ProvisionedConcurrency,
+            "snap-start" => InitializationType::SnapStart,
+            unknown => InitializationType::Unknown(unknown.to_string()),
+        }
+    }
+
+    #[instrument]
+    pub async fn from_environment() -> InitializationType {
+        let aws_lambda_initialization_type = OsString::from("AWS_LAMBDA_INITIALIZATION_TYPE");
+
+        match std::env::var_os(aws_lambda_initialization_type) {
+            Some(value) => {
+                let initialization_type =
+                    InitializationType::parse(value.to_string_lossy().as_ref()).await;
+                info!(
+                    "| kaon initialization | initialization type - {:?}",
+                    &initialization_type,
+                );
+                initialization_type
+            }
+            None => {
+                info!("| kaon initialization | initialization type not found, using on-demand");
+                InitializationType::OnDemand
+            }
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            InitializationType::OnDemand => "on-demand",
+            InitializationType::ProvisionedConcurrency => "provisioned-concurrency",
+            InitializationType::SnapStart => "snap-start",
+            InitializationType::Unknown(unknown) => unknown.as_str(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct Initialization {
+    pub cold_start: bool,
+    pub initialization_type: InitializationType,
+    pub duration: Duration,
+}
+
+impl Initialization {
+    pub async fn create(
+        first_invocation: bool,
+        initialization_type: InitializationType,
+        duration: Duration,
+    ) -> Initialization {
+        // provisioned concurrency initializes ahead of time, so the first invocation is already warm
+        let cold_start =
+            first_invocation && initialization_type != InitializationType::ProvisionedConcurrency;
+
+        Initialization {
+            cold_start,
+            initialization_type,
+            duration,
+        }
+    }
+}
+
+impl Default for Initialization {
+    fn default() -> Initialization {
+        Initialization {
+            cold_start: false,
+            initialization_type: InitializationType::OnDemand,
+            duration: Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn parse() {
+        assert_eq!(
+            InitializationType::parse("on-demand").await,
+            InitializationType::OnDemand,
+        );
+        assert_eq!(
+            InitializationType::parse("provisioned-concurrency").await,
+            InitializationType::ProvisionedConcurrency,
+        );
+        assert_eq!(
+            InitializationType::parse("snap-start").await,
+            InitializationType::SnapStart,
+        );
+        assert_eq!(
+            InitializationType::parse("test_initialization_type").await,
+            InitializationType::Unknown(String::from("test_initialization_type")),
+        );
+        assert_eq!(
+            InitializationType::parse("snap-start").await.as_str(),
+            "snap-start",
+        );
+    }
+
+    #[tokio::test]
+    async fn create() {
+        let test_duration = Duration::from_millis(250);
+
+        let test_on_demand =
+            Initialization::create(true, InitializationType::OnDemand, test_duration).await;
+        assert!(test_on_demand.cold_start);
+        assert_eq!(test_on_demand.duration, Duration::from_millis(250));
+
+        let test_warm =
+            Initialization::create(false, InitializationType::OnDemand, test_duration).await;
+        assert!(!test_warm.cold_start);
+
+        let test_snap_start =
+            Initialization::create(true, InitializationType::SnapStart, test_duration).await;
+        assert!(test_snap_start.cold_start);
+
+        let test_provisioned_concurrency = Initialization::create(
+            true,
+            InitializationType::ProvisionedConcurrency,
+            test_duration,
+        )
+        .await;
+        assert!(!test_provisioned_concurrency.cold_start);
+        assert_eq!(
+            test_provisioned_concurrency.initialization_type,
+            InitializationType::ProvisionedConcurrency,
+        );
+    }
+}
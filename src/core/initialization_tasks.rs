@@ -40,7 +40,7 @@ pub(crate) async fn retrieve_settings() -> String {
     ];
 
     let sensitive_environment_variables =
-        vec![aws_access_key_id, aws_secret_access_key, aws_session_token];
+        [aws_access_key_id, aws_secret_access_key, aws_session_token];
 
     for var in environment_variables.iter() {
         match std::env::var_os(var) {
@@ -0,0 +1,95 @@
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::context::Context;
+use crate::core::trace::TraceHeader;
+
+tokio::task_local! {
+    static CURRENT_INVOCATION: Invocation;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invocation {
+    pub aws_request_id: String,
+    pub trace_header: Option<TraceHeader>,
+    pub deadline_ms: Option<u64>,
+}
+
+impl Invocation {
+    pub async fn from_context(context: &Context) -> Invocation {
+        Invocation {
+            aws_request_id: context.aws_request_id.to_owned(),
+            trace_header: context.trace_header.clone(),
+            deadline_ms: context.deadline_ms,
+        }
+    }
+
+    pub async fn scope<Scoped: Future>(self, future: Scoped) -> Scoped::Output {
+        CURRENT_INVOCATION.scope(self, future).await
+    }
+
+    // synchronous so log formatters and other non-async code can read it, only the
+    // task polling the handler sees a value, tasks spawned from it do not inherit it
+    pub fn current() -> Option<Invocation> {
+        CURRENT_INVOCATION.try_with(Invocation::clone).ok()
+    }
+
+    pub fn with_current<Read, Output>(read: Read) -> Option<Output>
+    where
+        Read: FnOnce(&Invocation) -> Output,
+    {
+        CURRENT_INVOCATION.try_with(read).ok()
+    }
+
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.deadline_ms.map(|deadline_ms| {
+            (UNIX_EPOCH + Duration::from_millis(deadline_ms))
+                .duration_since(SystemTime::now())
+                .unwrap_or_default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_context;
+
+    #[tokio::test]
+    async fn scope() {
+        let test_trace_header = TraceHeader::parse(
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+        )
+        .await;
+        let mut test_context = test_context().await;
+        test_context.trace_header = test_trace_header;
+        test_context.deadline_ms = Some(1542409706888);
+
+        assert_eq!(Invocation::current(), None);
+
+        let test_invocation = Invocation::from_context(&test_context).await;
+        let test_current = test_invocation
+            .clone()
+            .scope(async {
+                tokio::task::yield_now().await;
+                let test_request_id =
+                    Invocation::with_current(|current| current.aws_request_id.to_owned());
+                assert_eq!(
+                    test_request_id,
+                    Some(String::from("8476a536-e9f4-11e8-9739-2dfe598c3fcd")),
+                );
+                Invocation::current()
+            })
+            .await;
+
+        assert_eq!(test_current, Some(test_invocation));
+        assert_eq!(Invocation::current(), None);
+
+        let test_current = test_current.unwrap();
+        assert_eq!(
+            test_current.trace_header.as_ref().unwrap().root,
+            "1-5759e988-bd862e3fe1be46a994272793",
+        );
+        assert_eq!(test_current.remaining_time(), Some(Duration::ZERO));
+    }
+}
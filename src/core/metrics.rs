@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum MetricUnit {
+    None,
+    Count,
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Bytes,
+    Kilobytes,
+    Megabytes,
+    Percent,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MetricKind {
+    Counter,
+    Gauge,
+    Histogram,
+}
+
+#[derive(Debug)]
+struct Metric {
+    kind: MetricKind,
+    unit: MetricUnit,
+    values: Vec<f64>,
+}
+
+#[derive(Debug, Default)]
+struct MetricsBuffer {
+    dimensions: BTreeMap<String, String>,
+    metrics: BTreeMap<String, Metric>,
+}
+
+impl MetricsBuffer {
+    fn record(&mut self, name: &str, kind: MetricKind, unit: MetricUnit, value: f64) {
+        let metric = self.metrics.entry(name.to_string()).or_insert(Metric {
+            kind,
+            unit,
+            values: Vec::with_capacity(1),
+        });
+
+        // merging would mix values of different kinds under one unit, so the sample is dropped
+        if metric.kind != kind {
+            error!(
+                "| kaon metrics | {} is a {:?}, dropping the {:?} sample",
+                name, metric.kind, kind,
+            );
+            return;
+        }
+
+        match (kind, metric.values.last_mut()) {
+            (MetricKind::Counter, Some(total)) => *total += value,
+            (MetricKind::Gauge, Some(last)) => *last = value,
+            _ => metric.values.push(value),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Metrics {
+    namespace: String,
+    buffer: Arc<Mutex<MetricsBuffer>>,
+}
+
+impl Metrics {
+    pub async fn create(namespace: &str) -> Metrics {
+        Metrics {
+            namespace: namespace.to_string(),
+            buffer: Arc::new(Mutex::new(MetricsBuffer::default())),
+        }
+    }
+
+    pub async fn counter(&self, name: &str, value: f64) {
+        self.record(name, MetricKind::Counter, MetricUnit::Count, value);
+    }
+
+    pub async fn gauge(&self, name: &str, value: f64, unit: MetricUnit) {
+        self.record(name, MetricKind::Gauge, unit, value);
+    }
+
+    pub async fn histogram(&self, name: &str, value: f64, unit: MetricUnit) {
+        self.record(name, MetricKind::Histogram, unit, value);
+    }
+
+    pub async fn dimension(&self, name: &str, value: &str) {
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer
+                .dimensions
+                .insert(name.to_string(), value.to_string());
+        }
+    }
+
+    fn record(&self, name: &str, kind: MetricKind, unit: MetricUnit, value: f64) {
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.record(name, kind, unit, value);
+        }
+    }
+
+    pub async fn flush(&self, duration: Duration, errors: usize) -> String {
+        let mut buffer = match self.buffer.lock() {
+            Ok(buffer) => buffer,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        buffer.record(
+            "InvocationDuration",
+            MetricKind::Histogram,
+            MetricUnit::Milliseconds,
+            duration.as_secs_f64() * 1000.0,
+        );
+        buffer.record(
+            "InvocationErrors",
+            MetricKind::Counter,
+            MetricUnit::Count,
+            errors as f64,
+        );
+
+        let buffered = std::mem::take(&mut *buffer);
+        drop(buffer);
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let mut definitions = Vec::with_capacity(buffered.metrics.len());
+        let mut document = Map::new();
+
+        for (name, value) in buffered.dimensions.iter() {
+            document.insert(name.to_owned(), Value::from(value.as_str()));
+        }
+
+        for (name, metric) in buffered.metrics.into_iter() {
+            definitions.push(json!({ "Name": name, "Unit": metric.unit }));
+            let value = match metric.values.as_slice() {
+                [single] => Value::from(*single),
+                values => Value::from(values.to_vec()),
+            };
+            document.insert(name, value);
+        }
+
+        let dimension_keys: Vec<&String> = buffered.dimensions.keys().collect();
+
+        document.insert(
+            String::from("_aws"),
+            json!({
+                "Timestamp": timestamp,
+                "CloudWatchMetrics": [{
+                    "Namespace": self.namespace,
+                    "Dimensions": [dimension_keys],
+                    "Metrics": definitions,
+                }],
+            }),
+        );
+
+        let emf = Value::Object(document).to_string();
+
+        println!("{}", emf);
+        info!("| kaon metrics | metrics flushed to {}", self.namespace);
+
+        emf
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Metrics {
+        Metrics {
+            namespace: String::from("kaon"),
+            buffer: Arc::new(Mutex::new(MetricsBuffer::default())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn flush() {
+        let test_metrics = Metrics::create("test_namespace").await;
+        test_metrics.dimension("Service", "test_service").await;
+        test_metrics.counter("Orders", 1.0).await;
+        test_metrics.counter("Orders", 2.0).await;
+        test_metrics
+            .gauge("QueueDepth", 10.0, MetricUnit::Count)
+            .await;
+        test_metrics
+            .gauge("QueueDepth", 4.0, MetricUnit::Count)
+            .await;
+        test_metrics
+            .histogram("PayloadSize", 128.0, MetricUnit::Bytes)
+            .await;
+        test_metrics
+            .histogram("PayloadSize", 256.0, MetricUnit::Bytes)
+            .await;
+
+        let test_emf = test_metrics.flush(Duration::from_millis(5), 1).await;
+        let test_document: Value = serde_json::from_str(&test_emf).unwrap();
+
+        assert_eq!(test_document["Service"], "test_service");
+        assert_eq!(test_document["Orders"], 3.0);
+        assert_eq!(test_document["QueueDepth"], 4.0);
+        assert_eq!(test_document["PayloadSize"], json!([128.0, 256.0]));
+        assert_eq!(test_document["InvocationDuration"], 5.0);
+        assert_eq!(test_document["InvocationErrors"], 1.0);
+
+        let test_directive = &test_document["_aws"]["CloudWatchMetrics"][0];
+        assert!(test_document["_aws"]["Timestamp"].is_u64());
+        assert_eq!(test_directive["Namespace"], "test_namespace");
+        assert_eq!(test_directive["Dimensions"], json!([["Service"]]));
+        assert_eq!(test_directive["Metrics"].as_array().unwrap().len(), 5);
+        assert!(test_directive["Metrics"]
+            .as_array()
+            .unwrap()
+            .contains(&json!({ "Name": "PayloadSize", "Unit": "Bytes" })));
+
+        test_metrics.counter("InvocationDuration", 1.0).await;
+        test_metrics
+            .histogram("Orders", 250.0, MetricUnit::Milliseconds)
+            .await;
+        test_metrics.counter("Orders", 1.0).await;
+        let test_mismatched_emf = test_metrics.flush(Duration::from_millis(7), 0).await;
+        let test_mismatched_document: Value = serde_json::from_str(&test_mismatched_emf).unwrap();
+        assert_eq!(test_mismatched_document["InvocationDuration"], 1.0);
+        assert_eq!(test_mismatched_document["Orders"], json!(250.0));
+        assert!(
+            test_mismatched_document["_aws"]["CloudWatchMetrics"][0]["Metrics"]
+                .as_array()
+                .unwrap()
+                .contains(&json!({ "Name": "Orders", "Unit": "Milliseconds" }))
+        );
+
+        let test_empty_emf = test_metrics.flush(Duration::from_millis(1), 0).await;
+        let test_empty_document: Value = serde_json::from_str(&test_empty_emf).unwrap();
+        assert!(test_empty_document.get("Orders").is_none());
+        assert_eq!(test_empty_document["InvocationErrors"], 0.0);
+        assert_eq!(
+            test_empty_document["_aws"]["CloudWatchMetrics"][0]["Dimensions"],
+            json!([[]]),
+        );
+    }
+}
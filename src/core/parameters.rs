@@ -0,0 +1,341 @@
+use hyper::body::Body;
+use hyper::client::connect::HttpConnector;
+use hyper::client::Client;
+use hyper::http::uri::Scheme;
+use hyper::{Request, Uri};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tracing::{error, info, instrument};
+
+use crate::core::invocation::Invocation;
+use crate::core::redaction::REDACTED;
+use crate::core::sigv4::uri_encode;
+
+const TOKEN_HEADER: &str = "X-Aws-Parameters-Secrets-Token";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParametersError {
+    InvalidRequest(String),
+    Transport(String),
+    Extension { status: u16, message: String },
+    Unmarshal(String),
+}
+
+impl fmt::Display for ParametersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParametersError::InvalidRequest(error) => write!(f, "invalid request - {}", error),
+            ParametersError::Transport(error) => write!(f, "extension unreachable - {}", error),
+            ParametersError::Extension { status, message } => {
+                write!(f, "extension responded {} - {}", status, message)
+            }
+            ParametersError::Unmarshal(error) => write!(f, "unexpected response - {}", error),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Parameters {
+    client: Client<HttpConnector, Body>,
+    authority: String,
+    token: Option<String>,
+    ttl: Duration,
+    cache: Arc<Mutex<HashMap<String, (Instant, String)>>>,
+}
+
+impl Default for Parameters {
+    fn default() -> Parameters {
+        Parameters {
+            client: Client::new(),
+            authority: format!("localhost:{}", Parameters::PORT),
+            token: None,
+            ttl: Parameters::TTL,
+            cache: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+impl Parameters {
+    pub const PORT: u16 = 2773;
+    pub const TTL: Duration = Duration::from_secs(300);
+
+    pub async fn create(authority: &str, token: Option<&str>) -> Parameters {
+        Parameters {
+            authority: authority.to_string(),
+            token: token.map(str::to_string),
+            ..Parameters::default()
+        }
+    }
+
+    pub async fn from_environment() -> Parameters {
+        let port = OsString::from("PARAMETERS_SECRETS_EXTENSION_HTTP_PORT");
+        let aws_session_token = OsString::from("AWS_SESSION_TOKEN");
+
+        let port = std::env::var_os(port)
+            .and_then(|value| value.to_string_lossy().parse::<u16>().ok())
+            .unwrap_or(Parameters::PORT);
+        let token = std::env::var_os(aws_session_token);
+
+        Parameters::create(
+            &format!("localhost:{}", port),
+            token
+                .as_ref()
+                .map(|value| value.to_string_lossy())
+                .as_deref(),
+        )
+        .await
+    }
+
+    pub async fn with_ttl(mut self, ttl: Duration) -> Parameters {
+        self.ttl = ttl;
+        self
+    }
+
+    pub async fn parameter(
+        &self,
+        name: &str,
+        with_decryption: bool,
+    ) -> Result<String, ParametersError> {
+        let path_and_query = format!(
+            "/systemsmanager/parameters/get?name={}&withDecryption={}",
+            uri_encode(name, true),
+            with_decryption,
+        );
+
+        self.cached(&path_and_query, "/Parameter/Value").await
+    }
+
+    pub async fn secret(&self, secret_id: &str) -> Result<String, ParametersError> {
+        let path_and_query = format!(
+            "/secretsmanager/get?secretId={}",
+            uri_encode(secret_id, true)
+        );
+
+        self.cached(&path_and_query, "/SecretString").await
+    }
+
+    pub async fn invalidate(&self) {
+        self.cache().clear();
+    }
+
+    pub async fn cached_len(&self) -> usize {
+        self.cache().len()
+    }
+
+    fn cache(&self) -> MutexGuard<'_, HashMap<String, (Instant, String)>> {
+        match self.cache.lock() {
+            Ok(cache) => cache,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    async fn cached(&self, path_and_query: &str, pointer: &str) -> Result<String, ParametersError> {
+        if let Some((fetched_at, value)) = self.cache().get(path_and_query) {
+            if fetched_at.elapsed() < self.ttl {
+                return Ok(value.clone());
+            }
+        }
+
+        let value = self.fetch(path_and_query, pointer).await?;
+        self.cache()
+            .insert(path_and_query.to_string(), (Instant::now(), value.clone()));
+
+        Ok(value)
+    }
+
+    #[instrument(skip(self, pointer))]
+    async fn fetch(&self, path_and_query: &str, pointer: &str) -> Result<String, ParametersError> {
+        let uri = Uri::builder()
+            .scheme(Scheme::HTTP)
+            .authority(self.authority.as_str())
+            .path_and_query(path_and_query)
+            .build()
+            .map_err(|error| ParametersError::InvalidRequest(error.to_string()))?;
+
+        let mut request = Request::get(uri);
+        if let Some(token) = &self.token {
+            request = request.header(TOKEN_HEADER, token.as_str());
+        }
+        let mut request = request
+            .body(Body::empty())
+            .map_err(|error| ParametersError::InvalidRequest(error.to_string()))?;
+
+        if let Some(trace_header) =
+            Invocation::with_current(|invocation| invocation.trace_header.clone()).flatten()
+        {
+            trace_header.propagate(request.headers_mut()).await;
+        }
+
+        let response = self
+            .client
+            .request(request)
+            .await
+            .map_err(|error| ParametersError::Transport(error.to_string()))?;
+        let status = response.status();
+        let body = hyper::body::to_bytes(response.into_body())
+            .await
+            .map_err(|error| ParametersError::Transport(error.to_string()))?;
+
+        if !status.is_success() {
+            let message = String::from_utf8_lossy(&body).trim().to_string();
+            error!(
+                "| kaon parameters | extension responded {} - {}",
+                status, message
+            );
+
+            return Err(ParametersError::Extension {
+                status: status.as_u16(),
+                message,
+            });
+        }
+
+        info!("| kaon parameters | fetched from the extension");
+
+        serde_json::from_slice::<Value>(&body)
+            .ok()
+            .and_then(|response| {
+                response
+                    .pointer(pointer)
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            })
+            .ok_or_else(|| ParametersError::Unmarshal(format!("response has no {}", pointer)))
+    }
+}
+
+impl fmt::Debug for Parameters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Parameters")
+            .field("authority", &self.authority)
+            .field("token", &self.token.as_ref().map(|_| REDACTED))
+            .field("ttl", &self.ttl)
+            .field("cached", &self.cache().len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::trace::TraceHeader;
+
+    #[tokio::test]
+    async fn parameter() {
+        let mut test_server = mockito::Server::new_async().await;
+
+        let mock = test_server
+            .mock("GET", "/systemsmanager/parameters/get")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("name".into(), "/test/db/password".into()),
+                mockito::Matcher::UrlEncoded("withDecryption".into(), "true".into()),
+            ]))
+            .match_header("X-Aws-Parameters-Secrets-Token", "test_session_token")
+            .match_header(
+                "X-Amzn-Trace-Id",
+                "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+            )
+            .with_body(
+                r#"{"Parameter": {"Name": "/test/db/password", "Type": "SecureString", "Value": "test_password"}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let mock_missing = test_server
+            .mock("GET", "/systemsmanager/parameters/get")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "name".into(),
+                "/test/missing".into(),
+            ))
+            .with_status(400)
+            .with_body("ParameterNotFound")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let test_parameters =
+            Parameters::create(&test_server.host_with_port(), Some("test_session_token")).await;
+        let test_invocation = Invocation {
+            aws_request_id: String::from("8476a536-e9f4-11e8-9739-2dfe598c3fcd"),
+            trace_header: TraceHeader::parse(
+                "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+            )
+            .await,
+            deadline_ms: None,
+        };
+
+        test_invocation
+            .scope(async {
+                for _ in 0..3 {
+                    let test_value = test_parameters
+                        .parameter("/test/db/password", true)
+                        .await
+                        .unwrap();
+                    assert_eq!(test_value, "test_password");
+                }
+            })
+            .await;
+        mock.assert_async().await;
+        assert_eq!(test_parameters.cached_len().await, 1);
+
+        let test_error = test_parameters
+            .parameter("/test/missing", false)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            test_error,
+            ParametersError::Extension {
+                status: 400,
+                message: String::from("ParameterNotFound"),
+            },
+        );
+        mock_missing.assert_async().await;
+        assert_eq!(test_parameters.cached_len().await, 1);
+
+        let test_debug = format!("{:?}", test_parameters);
+        assert!(!test_debug.contains("test_session_token"));
+        assert!(!test_debug.contains("test_password"));
+    }
+
+    #[tokio::test]
+    async fn secret() {
+        let mut test_server = mockito::Server::new_async().await;
+
+        let mock = test_server
+            .mock("GET", "/secretsmanager/get")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "secretId".into(),
+                "test/api key".into(),
+            ))
+            .with_body(r#"{"Name": "test/api key", "SecretString": "test_secret"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let test_parameters = Parameters::create(&test_server.host_with_port(), None)
+            .await
+            .with_ttl(Duration::from_secs(60))
+            .await;
+
+        assert_eq!(
+            test_parameters.secret("test/api key").await.unwrap(),
+            "test_secret"
+        );
+        assert_eq!(
+            test_parameters.secret("test/api key").await.unwrap(),
+            "test_secret"
+        );
+
+        // clones share the cache, so invalidating one refetches for all of them
+        test_parameters.clone().invalidate().await;
+        assert_eq!(test_parameters.cached_len().await, 0);
+        assert_eq!(
+            test_parameters.secret("test/api key").await.unwrap(),
+            "test_secret"
+        );
+        mock.assert_async().await;
+    }
+}
@@ -0,0 +1,342 @@
+use hyper::body::Bytes;
+use serde::de::{Deserialize, DeserializeOwned};
+use serde_json::Value;
+use serde_path_to_error::Segment;
+use std::fmt;
+
+const EXCERPT_RADIUS: usize = 24;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeserializationError {
+    pub path: String,
+    pub expected: Option<String>,
+    pub found: Option<String>,
+    pub excerpt: String,
+    pub message: String,
+}
+
+impl DeserializationError {
+    pub async fn create(
+        error: serde_path_to_error::Error<serde_json::Error>,
+        payload: &[u8],
+    ) -> DeserializationError {
+        let mut path = String::from("$");
+        let mut pointer = String::new();
+
+        for segment in error.path().iter() {
+            match segment {
+                Segment::Seq { index } => {
+                    path.push_str(&format!("[{}]", index));
+                    pointer.push_str(&format!("/{}", index));
+                }
+                Segment::Map { key } | Segment::Enum { variant: key } => {
+                    path.push_str(&format!(".{}", key));
+                    pointer.push_str(&format!("/{}", key.replace('~', "~0").replace('/', "~1")));
+                }
+                Segment::Unknown => path.push_str(".?"),
+            }
+        }
+
+        let inner = error.into_inner();
+        let location = format!(" at line {} column {}", inner.line(), inner.column());
+        let message = inner.to_string();
+        let message = message
+            .strip_suffix(&location)
+            .unwrap_or(&message)
+            .to_string();
+        let (expected, found) = expected_and_found(&message);
+
+        let excerpt = match inner.line() {
+            0 => node_excerpt(payload, &pointer),
+            line => window_excerpt(payload, line, inner.column()),
+        };
+
+        DeserializationError {
+            path,
+            expected,
+            found,
+            excerpt,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for DeserializationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "failed to deserialize the event at {}: ", self.path)?;
+
+        match (&self.expected, &self.found) {
+            (Some(expected), Some(found)) => write!(f, "expected {}, found {}", expected, found)?,
+            _ => write!(f, "{}", self.message)?,
+        }
+
+        write!(f, " near `{}`", self.excerpt)
+    }
+}
+
+// the raw event as received from the runtime api, handlers taking it can deserialize types
+// that borrow from the buffer instead of copying every string out of it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payload {
+    bytes: Bytes,
+}
+
+impl Payload {
+    pub fn bytes(&self) -> &Bytes {
+        &self.bytes
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub async fn parse<'de, EventRequest: Deserialize<'de>>(
+        &'de self,
+    ) -> Result<EventRequest, DeserializationError> {
+        deserialize(&self.bytes).await
+    }
+}
+
+impl From<Bytes> for Payload {
+    fn from(bytes: Bytes) -> Payload {
+        Payload { bytes }
+    }
+}
+
+// how the decay loop turns the buffered body into the handler's event
+pub(crate) trait Extract: Sized {
+    async fn extract(payload: Bytes) -> Result<Self, DeserializationError>;
+}
+
+impl<EventRequest: DeserializeOwned> Extract for EventRequest {
+    async fn extract(payload: Bytes) -> Result<EventRequest, DeserializationError> {
+        deserialize(&payload).await
+    }
+}
+
+impl Extract for Payload {
+    async fn extract(payload: Bytes) -> Result<Payload, DeserializationError> {
+        Ok(Payload::from(payload))
+    }
+}
+
+pub async fn deserialize<'de, EventRequest: Deserialize<'de>>(
+    payload: &'de [u8],
+) -> Result<EventRequest, DeserializationError> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(payload);
+
+    match serde_path_to_error::deserialize(deserializer) {
+        Ok(event) => Ok(event),
+        Err(error) => Err(DeserializationError::create(error, payload).await),
+    }
+}
+
+// the router has already parsed the payload, so errors carry no line or column and the
+// excerpt is taken from the value at the failing path instead
+pub fn deserialize_value<EventRequest: DeserializeOwned>(
+    event: &Value,
+) -> Result<EventRequest, serde_path_to_error::Error<serde_json::Error>> {
+    serde_path_to_error::deserialize(event)
+}
+
+fn expected_and_found(message: &str) -> (Option<String>, Option<String>) {
+    let mismatch = message
+        .strip_prefix("invalid type: ")
+        .or_else(|| message.strip_prefix("invalid value: "));
+
+    if let Some((found, expected)) =
+        mismatch.and_then(|mismatch| mismatch.split_once(", expected "))
+    {
+        return (Some(expected.to_string()), Some(redact(found)));
+    }
+
+    if let Some(field) = message.strip_prefix("missing field ") {
+        return (
+            Some(format!("field {}", field)),
+            Some(String::from("nothing")),
+        );
+    }
+
+    (None, None)
+}
+
+fn window_excerpt(payload: &[u8], line: usize, column: usize) -> String {
+    let text = String::from_utf8_lossy(payload);
+    let redacted = redact(text.lines().nth(line - 1).unwrap_or_default());
+    let characters: Vec<char> = redacted.chars().collect();
+    let position = column.min(characters.len());
+    let start = position.saturating_sub(EXCERPT_RADIUS);
+    let end = (position + EXCERPT_RADIUS).min(characters.len());
+
+    characters[start..end].iter().collect()
+}
+
+fn node_excerpt(payload: &[u8], pointer: &str) -> String {
+    let node = serde_json::from_slice::<Value>(payload)
+        .ok()
+        .and_then(|event| event.pointer(pointer).map(Value::to_string));
+    let node = redact(&node.unwrap_or_else(|| String::from_utf8_lossy(payload).into_owned()));
+
+    let mut excerpt: String = node.chars().take(EXCERPT_RADIUS * 2).collect();
+    if excerpt.len() < node.len() {
+        excerpt.push_str("...");
+    }
+    excerpt
+}
+
+// keys and structure stay readable, string values and numbers are masked character for
+// character so that columns reported by serde_json still line up with the redacted text
+fn redact(excerpt: &str) -> String {
+    let mut redacted = String::with_capacity(excerpt.len());
+    let mut characters = excerpt.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '"' => {
+                let mut literal = String::new();
+                let mut escaped = false;
+                let mut closed = false;
+
+                for character in characters.by_ref() {
+                    match character {
+                        '"' if !escaped => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' if !escaped => escaped = true,
+                        _ => escaped = false,
+                    }
+                    literal.push(character);
+                }
+
+                let key = characters
+                    .clone()
+                    .find(|next| !next.is_whitespace())
+                    .is_some_and(|next| next == ':');
+                redacted.push('"');
+                match key {
+                    true => redacted.push_str(&literal),
+                    false => redacted.push_str(&"*".repeat(literal.chars().count())),
+                }
+                if closed {
+                    redacted.push('"');
+                }
+            }
+            '0'..='9' => redacted.push('*'),
+            _ => redacted.push(character),
+        }
+    }
+
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct TestItem {
+        sku: String,
+        quantity: u32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Deserialize)]
+    struct TestOrder {
+        customer: String,
+        items: Vec<TestItem>,
+    }
+
+    #[tokio::test]
+    async fn deserialize() {
+        let test_payload = br#"{"customer": "test_secret_customer", "items": [{"sku": "test_sku", "quantity": 2}]}"#;
+        let test_order: TestOrder = super::deserialize(test_payload).await.unwrap();
+        assert_eq!(test_order.items[0].quantity, 2);
+
+        let test_payload = br#"{"customer": "test_secret_customer", "items": [{"sku": "test_sku", "quantity": "two"}]}"#;
+        let test_error = super::deserialize::<TestOrder>(test_payload)
+            .await
+            .unwrap_err();
+        assert_eq!(test_error.path, "$.items[0].quantity");
+        assert_eq!(test_error.expected.as_deref(), Some("u32"));
+        assert_eq!(test_error.found.as_deref(), Some("string \"***\""));
+        assert!(test_error.excerpt.contains(r#""quantity": "***""#));
+        assert!(!test_error.excerpt.contains("test_sku"));
+        assert!(test_error
+            .to_string()
+            .starts_with("failed to deserialize the event at $.items[0].quantity: expected u32"));
+
+        let test_error = super::deserialize::<TestOrder>(br#"{"items": []}"#)
+            .await
+            .unwrap_err();
+        assert_eq!(test_error.path, "$");
+        assert_eq!(test_error.expected.as_deref(), Some("field `customer`"));
+
+        let test_error = super::deserialize::<TestOrder>(b"{\"customer\": ")
+            .await
+            .unwrap_err();
+        assert_eq!(test_error.expected, None);
+        assert!(test_error.to_string().contains("EOF while parsing a value"));
+    }
+
+    #[tokio::test]
+    async fn deserialize_value() {
+        let test_payload =
+            br#"{"customer": "test_secret_customer", "items": [{"sku": 7, "quantity": 2}]}"#;
+        let test_event: Value = serde_json::from_slice(test_payload).unwrap();
+        let test_error = super::deserialize_value::<TestOrder>(&test_event).unwrap_err();
+        let test_error = DeserializationError::create(test_error, test_payload).await;
+        assert_eq!(test_error.path, "$.items[0].sku");
+        assert_eq!(test_error.expected.as_deref(), Some("a string"));
+        assert_eq!(test_error.found.as_deref(), Some("integer `*`"));
+        assert_eq!(test_error.excerpt, "*");
+    }
+
+    #[tokio::test]
+    async fn parse() {
+        #[derive(Debug, Deserialize)]
+        struct TestBorrowedOrder<'a> {
+            customer: &'a str,
+            #[serde(borrow)]
+            items: Vec<TestBorrowedItem<'a>>,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct TestBorrowedItem<'a> {
+            sku: &'a str,
+        }
+
+        let test_payload = Payload::from(Bytes::from_static(
+            br#"{"customer": "test_customer", "items": [{"sku": "test_sku"}]}"#,
+        ));
+        let test_order: TestBorrowedOrder = test_payload.parse().await.unwrap();
+        assert_eq!(test_order.customer, "test_customer");
+        assert_eq!(test_order.items[0].sku, "test_sku");
+
+        // the parsed strings point into the payload rather than copies of it
+        let test_range = test_payload.bytes().as_ptr_range();
+        assert!(test_range.contains(&test_order.customer.as_ptr()));
+
+        let test_error = test_payload.parse::<TestBorrowedItem>().await.unwrap_err();
+        assert_eq!(test_error.expected.as_deref(), Some("field `sku`"));
+
+        let test_payload = <Payload as Extract>::extract(Bytes::from_static(b"test_raw"))
+            .await
+            .unwrap();
+        assert_eq!(test_payload.len(), 8);
+    }
+
+    #[tokio::test]
+    async fn redact() {
+        assert_eq!(
+            super::redact(r#"{"token": "test_secret", "count": 12, "ok": true}"#),
+            r#"{"token": "***********", "count": **, "ok": true}"#,
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use tracing::info;
+
+use crate::core::context::Context;
+use crate::core::handler::HandlerError;
+use crate::core::Kaon;
+
+pub type DecayFuture<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+type Entry = Box<dyn for<'a> FnOnce(&'a mut Kaon) -> DecayFuture<'a>>;
+
+fn entry<Decay>(decay: Decay) -> Decay
+where
+    Decay: for<'a> FnOnce(&'a mut Kaon) -> DecayFuture<'a>,
+{
+    decay
+}
+
+#[derive(Default)]
+pub struct HandlerRegistry {
+    handlers: HashMap<String, Entry>,
+}
+
+impl HandlerRegistry {
+    pub async fn register<EventFunction, EventRequest, EventResponse, EventError, Outatime>(
+        &mut self,
+        name: &str,
+        function: EventFunction,
+    ) where
+        EventRequest: DeserializeOwned + 'static,
+        EventResponse: Serialize + 'static,
+        EventFunction: Fn(EventRequest, Context) -> Outatime + 'static,
+        EventError: Into<HandlerError> + 'static,
+        Outatime: Future<Output = Result<EventResponse, EventError>> + 'static,
+    {
+        info!("| kaon registry | registered {}", name);
+
+        self.handlers.insert(
+            name.to_string(),
+            Box::new(entry(move |kaon| Box::pin(kaon.decay(function)))),
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.handlers.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    pub async fn decay(mut self, kaon: &mut Kaon, name: &str) -> Result<(), String> {
+        match self.handlers.remove(name) {
+            Some(decay) => {
+                info!("| kaon registry | dispatching to {}", name);
+                decay(kaon).await;
+                Ok(())
+            }
+            None => Err(format!(
+                "no handler registered for {}, registered handlers are {:?}",
+                name,
+                self.names(),
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for HandlerRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct TestRequest {}
+
+    async fn test_orders(_event: TestRequest, _context: Context) -> Result<String, ()> {
+        Ok(String::from("test_orders"))
+    }
+
+    async fn test_payments(_event: TestRequest, _context: Context) -> Result<u32, ()> {
+        Ok(7)
+    }
+
+    #[tokio::test]
+    async fn register() {
+        let mut test_registry = HandlerRegistry::default();
+        assert!(test_registry.is_empty());
+
+        test_registry.register("orders.create", test_orders).await;
+        test_registry
+            .register("payments.capture", test_payments)
+            .await;
+
+        assert_eq!(test_registry.len(), 2);
+        assert!(test_registry.contains("orders.create"));
+        assert!(!test_registry.contains("orders.delete"));
+        assert_eq!(
+            test_registry.names(),
+            vec!["orders.create", "payments.capture"],
+        );
+        assert_eq!(
+            format!("{:?}", test_registry),
+            r#"["orders.create", "payments.capture"]"#,
+        );
+    }
+}
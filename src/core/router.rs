@@ -0,0 +1,310 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use tracing::info;
+
+use crate::core::context::Context;
+use crate::core::handler::HandlerError;
+use crate::core::payload::{self, DeserializationError};
+
+pub(crate) type RouteFuture = Pin<Box<dyn Future<Output = Result<Value, HandlerError>>>>;
+
+type Route = Box<dyn Fn(Value, Context) -> RouteFuture>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum EventSource {
+    Sqs,
+    Sns,
+    S3,
+    DynamoDb,
+    Kinesis,
+    EventBridge,
+    ApiGateway,
+    Unknown(String),
+}
+
+impl EventSource {
+    pub fn sniff(event: &Value) -> Option<EventSource> {
+        if let Some(record) = event.pointer("/Records/0") {
+            let source = record
+                .get("eventSource")
+                .or_else(|| record.get("EventSource"))
+                .and_then(Value::as_str)?;
+
+            return Some(match source {
+                "aws:sqs" => EventSource::Sqs,
+                "aws:sns" => EventSource::Sns,
+                "aws:s3" => EventSource::S3,
+                "aws:dynamodb" => EventSource::DynamoDb,
+                "aws:kinesis" => EventSource::Kinesis,
+                source => EventSource::Unknown(source.to_string()),
+            });
+        }
+
+        if event.get("detail-type").is_some() && event.get("source").is_some() {
+            return Some(EventSource::EventBridge);
+        }
+
+        if event.get("requestContext").is_some() {
+            return Some(EventSource::ApiGateway);
+        }
+
+        None
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            EventSource::Sqs => "aws:sqs",
+            EventSource::Sns => "aws:sns",
+            EventSource::S3 => "aws:s3",
+            EventSource::DynamoDb => "aws:dynamodb",
+            EventSource::Kinesis => "aws:kinesis",
+            EventSource::EventBridge => "aws:events",
+            EventSource::ApiGateway => "aws:apigateway",
+            EventSource::Unknown(source) => source.as_str(),
+        }
+    }
+}
+
+fn route<EventFunction, EventRequest, EventResponse, EventError, Outatime>(
+    function: EventFunction,
+) -> Route
+where
+    EventRequest: DeserializeOwned + 'static,
+    EventResponse: Serialize + 'static,
+    EventFunction: Fn(EventRequest, Context) -> Outatime + 'static,
+    EventError: Into<HandlerError> + 'static,
+    Outatime: Future<Output = Result<EventResponse, EventError>> + 'static,
+{
+    Box::new(
+        move |event, context| match payload::deserialize_value::<EventRequest>(&event) {
+            Ok(request) => {
+                let handling = function(request, context);
+
+                Box::pin(async move {
+                    let response = handling.await.map_err(Into::into)?;
+                    serde_json::to_value(response).map_err(|error| {
+                        HandlerError::Marshal(format!(
+                            "failed to serialize the handler response - {}",
+                            error,
+                        ))
+                    })
+                })
+            }
+            Err(error) => Box::pin(async move {
+                let payload = event.to_string();
+                let error = DeserializationError::create(error, payload.as_bytes()).await;
+                Err(HandlerError::Unmarshal(error.to_string()))
+            }),
+        },
+    )
+}
+
+#[derive(Default)]
+pub struct EventRouter {
+    routes: HashMap<EventSource, Route>,
+    fallback: Option<Route>,
+}
+
+impl EventRouter {
+    pub async fn route<EventFunction, EventRequest, EventResponse, EventError, Outatime>(
+        &mut self,
+        source: EventSource,
+        function: EventFunction,
+    ) where
+        EventRequest: DeserializeOwned + 'static,
+        EventResponse: Serialize + 'static,
+        EventFunction: Fn(EventRequest, Context) -> Outatime + 'static,
+        EventError: Into<HandlerError> + 'static,
+        Outatime: Future<Output = Result<EventResponse, EventError>> + 'static,
+    {
+        info!("| kaon router | routing {} events", source.as_str());
+        self.routes.insert(source, route(function));
+    }
+
+    pub async fn fallback<EventFunction, EventRequest, EventResponse, EventError, Outatime>(
+        &mut self,
+        function: EventFunction,
+    ) where
+        EventRequest: DeserializeOwned + 'static,
+        EventResponse: Serialize + 'static,
+        EventFunction: Fn(EventRequest, Context) -> Outatime + 'static,
+        EventError: Into<HandlerError> + 'static,
+        Outatime: Future<Output = Result<EventResponse, EventError>> + 'static,
+    {
+        info!("| kaon router | routing unmatched events to the fallback");
+        self.fallback = Some(route(function));
+    }
+
+    pub(crate) fn dispatch(&self, event: Value, context: Context) -> RouteFuture {
+        // routes are picked synchronously so the returned future owns everything it needs
+        let source = EventSource::sniff(&event);
+
+        let route = source
+            .as_ref()
+            .and_then(|source| self.routes.get(source))
+            .or(self.fallback.as_ref());
+
+        match route {
+            Some(route) => route(event, context),
+            None => {
+                let source = match source {
+                    Some(source) => source.as_str().to_string(),
+                    None => String::from("unrecognized"),
+                };
+                let error_message = format!("no route matches the {} event source", source);
+
+                Box::pin(async move { Err(HandlerError::Unmarshal(error_message)) })
+            }
+        }
+    }
+}
+
+impl fmt::Debug for EventRouter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventRouter")
+            .field("routes", &self.routes.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_context;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Deserialize)]
+    struct TestSqsEvent {
+        #[serde(rename = "Records")]
+        records: Vec<Value>,
+    }
+
+    #[derive(Deserialize)]
+    struct TestEventBridgeEvent {
+        #[serde(rename = "detail-type")]
+        detail_type: String,
+    }
+
+    async fn test_sqs(event: TestSqsEvent, _context: Context) -> Result<usize, ()> {
+        Ok(event.records.len())
+    }
+
+    async fn test_event_bridge(
+        event: TestEventBridgeEvent,
+        _context: Context,
+    ) -> Result<String, ()> {
+        Ok(event.detail_type)
+    }
+
+    async fn test_fallback(_event: Value, _context: Context) -> Result<&'static str, ()> {
+        Ok("test_fallback")
+    }
+
+    #[tokio::test]
+    async fn sniff() {
+        let test_events = [
+            (
+                json!({ "Records": [{ "eventSource": "aws:sqs" }] }),
+                Some(EventSource::Sqs),
+            ),
+            (
+                json!({ "Records": [{ "EventSource": "aws:sns" }] }),
+                Some(EventSource::Sns),
+            ),
+            (
+                json!({ "Records": [{ "eventSource": "aws:s3" }] }),
+                Some(EventSource::S3),
+            ),
+            (
+                json!({ "Records": [{ "eventSource": "aws:dynamodb" }] }),
+                Some(EventSource::DynamoDb),
+            ),
+            (
+                json!({ "Records": [{ "eventSource": "aws:kafka" }] }),
+                Some(EventSource::Unknown(String::from("aws:kafka"))),
+            ),
+            (
+                json!({ "detail-type": "OrderPlaced", "source": "test.orders" }),
+                Some(EventSource::EventBridge),
+            ),
+            (
+                json!({ "requestContext": { "http": { "method": "GET" } } }),
+                Some(EventSource::ApiGateway),
+            ),
+            (json!({ "test_request": "hello" }), None),
+            (json!("test_request"), None),
+        ];
+
+        for (test_event, test_source) in test_events {
+            assert_eq!(EventSource::sniff(&test_event), test_source);
+        }
+    }
+
+    #[tokio::test]
+    async fn dispatch() {
+        let mut test_router = EventRouter::default();
+        test_router.route(EventSource::Sqs, test_sqs).await;
+        test_router
+            .route(EventSource::EventBridge, test_event_bridge)
+            .await;
+
+        let test_sqs_event =
+            json!({ "Records": [{ "eventSource": "aws:sqs" }, { "eventSource": "aws:sqs" }] });
+        let test_response = test_router
+            .dispatch(test_sqs_event, test_context().await)
+            .await;
+        assert_eq!(test_response, Ok(json!(2)));
+
+        let test_event_bridge_event =
+            json!({ "detail-type": "OrderPlaced", "source": "test.orders" });
+        let test_response = test_router
+            .dispatch(test_event_bridge_event, test_context().await)
+            .await;
+        assert_eq!(test_response, Ok(json!("OrderPlaced")));
+
+        let test_response = test_router
+            .dispatch(json!({ "Records": "test_malformed" }), test_context().await)
+            .await;
+        assert_eq!(
+            test_response,
+            Err(HandlerError::Unmarshal(String::from(
+                "no route matches the unrecognized event source"
+            ))),
+        );
+
+        let test_response = test_router
+            .dispatch(
+                json!({ "requestContext": {}, "body": "hello" }),
+                test_context().await,
+            )
+            .await;
+        assert_eq!(
+            test_response,
+            Err(HandlerError::Unmarshal(String::from(
+                "no route matches the aws:apigateway event source"
+            ))),
+        );
+
+        let test_response = test_router
+            .dispatch(
+                json!({ "detail-type": 7, "source": "test.orders" }),
+                test_context().await,
+            )
+            .await;
+        assert!(matches!(test_response, Err(HandlerError::Unmarshal(_))));
+
+        test_router.fallback(test_fallback).await;
+        let test_response = test_router
+            .dispatch(json!({ "test_request": "hello" }), test_context().await)
+            .await;
+        assert_eq!(test_response, Ok(json!("test_fallback")));
+        assert!(format!("{:?}", test_router).contains("fallback: true"));
+    }
+}
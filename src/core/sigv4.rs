@@ -0,0 +1,458 @@
+use hmac::{Hmac, Mac};
+use hyper::body::{Body, Bytes};
+use hyper::header::{HeaderValue, AUTHORIZATION, HOST};
+use hyper::Request;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::core::credentials::Credentials;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SigningError {
+    Expired,
+    InvalidRequest(String),
+}
+
+impl fmt::Display for SigningError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SigningError::Expired => write!(f, "the credentials have expired"),
+            SigningError::InvalidRequest(error) => write!(f, "cannot sign the request - {}", error),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Signer {
+    credentials: Credentials,
+    region: String,
+    service: String,
+}
+
+impl Signer {
+    pub async fn create(credentials: Credentials, region: &str, service: &str) -> Signer {
+        Signer {
+            credentials,
+            region: region.to_string(),
+            service: service.to_string(),
+        }
+    }
+
+    pub fn credentials(&self) -> &Credentials {
+        &self.credentials
+    }
+
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    pub async fn sign(&self, request: Request<Bytes>) -> Result<Request<Body>, SigningError> {
+        self.sign_at(request, SystemTime::now()).await
+    }
+
+    pub async fn sign_at(
+        &self,
+        request: Request<Bytes>,
+        signed_at: SystemTime,
+    ) -> Result<Request<Body>, SigningError> {
+        if self.credentials.is_expired() {
+            return Err(SigningError::Expired);
+        }
+
+        let (mut parts, body) = request.into_parts();
+        let amz_date = format_timestamp(signed_at);
+        let date = &amz_date[..8];
+        let payload_hash = hex::encode(Sha256::digest(&body));
+
+        if !parts.headers.contains_key(HOST) {
+            let authority = parts.uri.authority().ok_or_else(|| {
+                SigningError::InvalidRequest(String::from("the uri has no authority"))
+            })?;
+            parts
+                .headers
+                .insert(HOST, header_value(authority.as_str())?);
+        }
+
+        parts.headers.insert("x-amz-date", header_value(&amz_date)?);
+
+        if let Some(session_token) = &self.credentials.session_token {
+            parts
+                .headers
+                .insert("x-amz-security-token", header_value(session_token)?);
+        }
+
+        // S3 requires the payload hash to be sent as well as signed
+        if self.service == "s3" {
+            parts
+                .headers
+                .insert("x-amz-content-sha256", header_value(&payload_hash)?);
+        }
+
+        let mut headers: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for (name, value) in parts.headers.iter() {
+            let value = value
+                .to_str()
+                .map_err(|error| SigningError::InvalidRequest(error.to_string()))?;
+
+            headers
+                .entry(name.as_str())
+                .or_default()
+                .push(value.split_whitespace().collect::<Vec<&str>>().join(" "));
+        }
+
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, values)| format!("{}:{}\n", name, values.join(",")))
+            .collect();
+        let signed_headers = headers.keys().copied().collect::<Vec<&str>>().join(";");
+
+        // S3 paths are signed as sent, every other service signs them encoded a second time
+        let canonical_uri = match self.service.as_str() {
+            "s3" => parts.uri.path().to_string(),
+            _ => uri_encode(parts.uri.path(), false),
+        };
+
+        let canonical_request = [
+            parts.method.as_str(),
+            &canonical_uri,
+            &canonical_query(parts.uri.query().unwrap_or_default()),
+            &canonical_headers,
+            &signed_headers,
+            &payload_hash,
+        ]
+        .join("\n");
+
+        let scope = format!("{}/{}/{}/aws4_request", date, self.region, self.service);
+        let string_to_sign = format!(
+            "{}\n{}\n{}\n{}",
+            ALGORITHM,
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes())),
+        );
+
+        let signing_key = [self.region.as_str(), self.service.as_str(), "aws4_request"]
+            .iter()
+            .fold(
+                hmac(
+                    format!("AWS4{}", self.credentials.secret_access_key).as_bytes(),
+                    date,
+                ),
+                |key, part| hmac(&key, part),
+            );
+        let signature = hex::encode(hmac(&signing_key, &string_to_sign));
+
+        let authorization = format!(
+            "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+            ALGORITHM, self.credentials.access_key_id, scope, signed_headers, signature,
+        );
+        parts
+            .headers
+            .insert(AUTHORIZATION, header_value(&authorization)?);
+
+        Ok(Request::from_parts(parts, Body::from(body)))
+    }
+}
+
+fn hmac(key: &[u8], message: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts keys of any length");
+    mac.update(message.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn header_value(value: &str) -> Result<HeaderValue, SigningError> {
+    HeaderValue::from_str(value).map_err(|error| SigningError::InvalidRequest(error.to_string()))
+}
+
+fn canonical_query(query: &str) -> String {
+    let mut pairs: Vec<(String, String)> = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (
+                uri_encode(&uri_decode(key), true),
+                uri_encode(&uri_decode(value), true),
+            )
+        })
+        .collect();
+    pairs.sort();
+
+    pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+pub(crate) fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+fn uri_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = bytes.get(index + 1..index + 3).and_then(|hex| {
+            std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        });
+
+        match (bytes[index], escaped) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// ISO 8601 basic format as used by SigV4, e.g. 20150830T123600Z
+pub(crate) fn format_timestamp(timestamp: SystemTime) -> String {
+    let seconds = timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64;
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let seconds = seconds.rem_euclid(86400);
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60,
+    )
+}
+
+// RFC 3339 in UTC, e.g. 2015-08-30T12:36:00Z or 2015-08-30T12:36:00.000Z
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<SystemTime> {
+    let timestamp = timestamp
+        .strip_suffix('Z')
+        .or_else(|| timestamp.strip_suffix("+00:00"))?;
+    let (date, time) = timestamp.split_once('T')?;
+
+    let date: Vec<i64> = date
+        .split('-')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let time: Vec<&str> = time.split(':').collect();
+    let (year, month, day) = match date[..] {
+        [year, month @ 1..=12, day @ 1..=31] => (year, month, day),
+        _ => return None,
+    };
+    let (hours, minutes, seconds) = match time[..] {
+        [hours, minutes, seconds] => (
+            hours.parse::<i64>().ok().filter(|hours| *hours < 24)?,
+            minutes
+                .parse::<i64>()
+                .ok()
+                .filter(|minutes| *minutes < 60)?,
+            seconds
+                .split('.')
+                .next()?
+                .parse::<i64>()
+                .ok()
+                .filter(|seconds| *seconds < 61)?,
+        ),
+        _ => return None,
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86400 + hours * 3600 + minutes * 60 + seconds;
+
+    Some(UNIX_EPOCH + Duration::from_secs(u64::try_from(seconds).ok()?))
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_signer(session_token: Option<&str>, service: &str) -> Signer {
+        let test_credentials = Credentials::create(
+            "AKIDEXAMPLE",
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            session_token,
+        )
+        .await;
+
+        Signer::create(test_credentials, "us-east-1", service).await
+    }
+
+    fn test_signed_at() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1440938160)
+    }
+
+    fn test_authorization(request: &Request<Body>) -> &str {
+        request
+            .headers()
+            .get(AUTHORIZATION)
+            .unwrap()
+            .to_str()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sign() {
+        let test_signer = test_signer(None, "service").await;
+
+        // requests from the AWS SigV4 test suite
+        let test_request = Request::get("https://example.amazonaws.com/")
+            .body(Bytes::new())
+            .unwrap();
+        let test_request = test_signer
+            .sign_at(test_request, test_signed_at())
+            .await
+            .unwrap();
+        assert_eq!(
+            test_authorization(&test_request),
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31",
+        );
+        assert_eq!(test_request.headers()["x-amz-date"], "20150830T123600Z");
+
+        let test_request =
+            Request::get("https://example.amazonaws.com/?Param2=value2&Param1=value1")
+                .body(Bytes::new())
+                .unwrap();
+        let test_request = test_signer
+            .sign_at(test_request, test_signed_at())
+            .await
+            .unwrap();
+        assert!(test_authorization(&test_request).ends_with(
+            "Signature=b97d918cfa904a5beff61c982a1b6f458b799221646efd99d3219ec94cdf2500"
+        ));
+    }
+
+    #[tokio::test]
+    async fn sign_session() {
+        let test_signer = test_signer(Some("test_session_token"), "s3").await;
+
+        let test_request = Request::put("https://test-bucket.s3.amazonaws.com/test%20key")
+            .header("content-type", "text/plain")
+            .body(Bytes::from_static(b"test_body"))
+            .unwrap();
+        let test_request = test_signer
+            .sign_at(test_request, test_signed_at())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            test_request.headers()["x-amz-security-token"],
+            "test_session_token",
+        );
+        assert_eq!(
+            test_request.headers()["x-amz-content-sha256"],
+            hex::encode(Sha256::digest(b"test_body")),
+        );
+        assert!(test_authorization(&test_request).contains(
+            "SignedHeaders=content-type;host;x-amz-content-sha256;x-amz-date;x-amz-security-token,"
+        ));
+
+        let test_signer = Signer::create(
+            test_signer
+                .credentials()
+                .clone()
+                .with_expiration(SystemTime::now() - Duration::from_secs(1))
+                .await,
+            "us-east-1",
+            "s3",
+        )
+        .await;
+        let test_request = Request::get("https://test-bucket.s3.amazonaws.com/")
+            .body(Bytes::new())
+            .unwrap();
+        assert_eq!(
+            test_signer.sign(test_request).await.unwrap_err(),
+            SigningError::Expired,
+        );
+    }
+
+    #[tokio::test]
+    async fn encode() {
+        assert_eq!(uri_encode("/test path/a+b", false), "/test%20path/a%2Bb");
+        assert_eq!(uri_encode("a/b=c", true), "a%2Fb%3Dc");
+        assert_eq!(uri_decode("test%20path%2"), "test path%2");
+        assert_eq!(canonical_query("b=2&a=%7E1&a=0&flag"), "a=0&a=~1&b=2&flag=",);
+    }
+
+    #[tokio::test]
+    async fn timestamp() {
+        assert_eq!(format_timestamp(test_signed_at()), "20150830T123600Z");
+        assert_eq!(format_timestamp(UNIX_EPOCH), "19700101T000000Z");
+        assert_eq!(
+            parse_timestamp("2015-08-30T12:36:00Z"),
+            Some(test_signed_at()),
+        );
+        assert_eq!(
+            parse_timestamp("2015-08-30T12:36:00.250+00:00"),
+            Some(test_signed_at()),
+        );
+        assert_eq!(
+            parse_timestamp("2024-02-29T23:59:59Z").map(format_timestamp),
+            Some(String::from("20240229T235959Z")),
+        );
+        assert_eq!(parse_timestamp("2015-13-30T12:36:00Z"), None);
+        assert_eq!(parse_timestamp("2015-08-30 12:36:00"), None);
+    }
+}
@@ -0,0 +1,374 @@
+use hyper::body::Body;
+use hyper::client::connect::HttpConnector;
+use hyper::client::Client;
+use hyper::{Request, Uri};
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::ffi::OsString;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{error, info, instrument};
+
+use crate::core::context::Context;
+use crate::core::trace::TraceHeader;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TelemetryError {
+    Transport(String),
+    Collector { status: u16, message: String },
+}
+
+impl fmt::Display for TelemetryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TelemetryError::Transport(error) => write!(f, "collector unreachable - {}", error),
+            TelemetryError::Collector { status, message } => {
+                write!(f, "collector responded {} - {}", status, message)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Telemetry {
+    client: Client<HttpConnector, Body>,
+    endpoint: Uri,
+    resource: Vec<Value>,
+    spans: Mutex<Vec<Value>>,
+}
+
+impl Telemetry {
+    pub async fn create(endpoint: &str) -> Option<Telemetry> {
+        let traces_endpoint = if endpoint.ends_with("/v1/traces") {
+            endpoint.to_string()
+        } else {
+            format!("{}/v1/traces", endpoint.trim_end_matches('/'))
+        };
+
+        match traces_endpoint.parse::<Uri>() {
+            Ok(endpoint) => {
+                info!("| kaon telemetry | exporting spans to {}", &endpoint);
+                Some(Telemetry {
+                    client: Client::new(),
+                    endpoint,
+                    resource: resource_attributes(),
+                    spans: Mutex::new(Vec::with_capacity(1)),
+                })
+            }
+            Err(error) => {
+                error!(
+                    "| kaon telemetry | invalid endpoint {} - {}",
+                    endpoint, error
+                );
+                None
+            }
+        }
+    }
+
+    #[instrument]
+    pub async fn from_environment() -> Option<Telemetry> {
+        let traces_endpoint = OsString::from("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT");
+        let endpoint = OsString::from("OTEL_EXPORTER_OTLP_ENDPOINT");
+
+        match std::env::var_os(traces_endpoint).or_else(|| std::env::var_os(endpoint)) {
+            Some(value) => Telemetry::create(value.to_string_lossy().as_ref()).await,
+            None => {
+                info!("| kaon telemetry | no OTLP endpoint configured, telemetry disabled");
+                None
+            }
+        }
+    }
+
+    async fn spans(&self) -> MutexGuard<'_, Vec<Value>> {
+        match self.spans.lock() {
+            Ok(spans) => spans,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    pub async fn record(
+        &self,
+        context: &Context,
+        started: SystemTime,
+        duration: Duration,
+        error: Option<&str>,
+    ) {
+        // x-ray already decided against sampling this trace upstream
+        if let Some(TraceHeader {
+            sampled: Some(false),
+            ..
+        }) = &context.trace_header
+        {
+            info!("| kaon telemetry | trace is not sampled, skipping the span");
+            return;
+        }
+
+        let (trace_id, parent_span_id) = match &context.trace_header {
+            Some(trace_header) => (
+                trace_header.root.split('-').skip(1).collect(),
+                trace_header.parent.clone().unwrap_or_default(),
+            ),
+            None => (random_id(32), String::new()),
+        };
+
+        let start = started
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let end = start + duration.as_nanos();
+
+        let mut attributes = vec![
+            attribute(
+                "faas.invocation_id",
+                json!({ "stringValue": context.aws_request_id }),
+            ),
+            attribute(
+                "faas.coldstart",
+                json!({ "boolValue": context.initialization.cold_start }),
+            ),
+            attribute(
+                "cloud.resource_id",
+                json!({ "stringValue": context.invoked_function_arn }),
+            ),
+            attribute(
+                "kaon.duration_ms",
+                json!({ "doubleValue": duration.as_secs_f64() * 1000.0 }),
+            ),
+        ];
+
+        let status = match error {
+            Some(message) => {
+                attributes.push(attribute(
+                    "error.message",
+                    json!({ "stringValue": message }),
+                ));
+                json!({ "code": 2, "message": message })
+            }
+            None => json!({ "code": 1 }),
+        };
+
+        self.spans().await.push(json!({
+            "traceId": trace_id,
+            "spanId": random_id(16),
+            "parentSpanId": parent_span_id,
+            "name": "kaon.invocation",
+            "kind": 2,
+            "startTimeUnixNano": start.to_string(),
+            "endTimeUnixNano": end.to_string(),
+            "attributes": attributes,
+            "status": status,
+        }));
+    }
+
+    pub async fn flush(&self) -> Result<(), TelemetryError> {
+        let spans = std::mem::take(&mut *self.spans().await);
+        if spans.is_empty() {
+            return Ok(());
+        }
+
+        let export = json!({
+            "resourceSpans": [{
+                "resource": { "attributes": self.resource },
+                "scopeSpans": [{
+                    "scope": { "name": "kaon", "version": env!("CARGO_PKG_VERSION") },
+                    "spans": spans,
+                }],
+            }],
+        });
+
+        let request = Request::builder()
+            .method("POST")
+            .uri(self.endpoint.clone())
+            .header("content-type", "application/json")
+            .body(Body::from(export.to_string()))
+            .unwrap();
+
+        let response = match self.client.request(request).await {
+            Ok(response) => response,
+            Err(error) => {
+                let error = TelemetryError::Transport(error.to_string());
+                error!("| kaon telemetry | {}", error);
+                return Err(error);
+            }
+        };
+
+        let status = response.status();
+        if status.is_success() {
+            info!("| kaon telemetry | spans exported {:?}", status);
+            return Ok(());
+        }
+
+        // the collector explains rejections in the body, the spans themselves are not retried
+        let message = match hyper::body::to_bytes(response.into_body()).await {
+            Ok(body) => String::from_utf8_lossy(&body).into_owned(),
+            Err(error) => error.to_string(),
+        };
+        let error = TelemetryError::Collector {
+            status: status.as_u16(),
+            message,
+        };
+        error!("| kaon telemetry | {}", error);
+        Err(error)
+    }
+}
+
+fn attribute(key: &str, value: Value) -> Value {
+    json!({ "key": key, "value": value })
+}
+
+fn resource_attributes() -> Vec<Value> {
+    let service_name =
+        std::env::var("AWS_LAMBDA_FUNCTION_NAME").unwrap_or_else(|_| String::from("kaon"));
+    let mut attributes = vec![
+        attribute("service.name", json!({ "stringValue": service_name })),
+        attribute("cloud.provider", json!({ "stringValue": "aws" })),
+        attribute("cloud.platform", json!({ "stringValue": "aws_lambda" })),
+    ];
+
+    if let Ok(region) = std::env::var("AWS_REGION") {
+        attributes.push(attribute("cloud.region", json!({ "stringValue": region })));
+    }
+
+    if let Ok(version) = std::env::var("AWS_LAMBDA_FUNCTION_VERSION") {
+        attributes.push(attribute("faas.version", json!({ "stringValue": version })));
+    }
+
+    attributes
+}
+
+fn random_id(length: usize) -> String {
+    let mut id = String::with_capacity(length);
+
+    while id.len() < length {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        );
+        id.push_str(&format!("{:016x}", hasher.finish()));
+    }
+
+    id.truncate(length);
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::context::test_context;
+    use mockito::Matcher;
+
+    #[tokio::test]
+    async fn random_id() {
+        assert_eq!(super::random_id(16).len(), 16);
+        assert_eq!(super::random_id(32).len(), 32);
+        assert_ne!(super::random_id(16), super::random_id(16));
+    }
+
+    #[tokio::test]
+    async fn record_and_flush() {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_endpoint = format!("http://{}", test_server.host_with_port());
+        let test_telemetry = Telemetry::create(&test_endpoint).await.unwrap();
+        assert_eq!(test_telemetry.endpoint.path(), "/v1/traces");
+
+        let test_trace_header = TraceHeader::parse(
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+        )
+        .await;
+        let mut test_context = test_context().await;
+        test_context.trace_header = test_trace_header;
+
+        let mock = test_server
+            .mock("POST", "/v1/traces")
+            .match_header("content-type", "application/json")
+            .match_body(Matcher::PartialJson(json!({
+                "resourceSpans": [{
+                    "scopeSpans": [{
+                        "scope": { "name": "kaon" },
+                        "spans": [{
+                            "traceId": "5759e988bd862e3fe1be46a994272793",
+                            "parentSpanId": "53995c3f42cd8ad8",
+                            "name": "kaon.invocation",
+                            "status": { "code": 2, "message": "test_error" },
+                        }],
+                    }],
+                }],
+            })))
+            .expect(1)
+            .create_async()
+            .await;
+
+        test_telemetry
+            .record(
+                &test_context,
+                SystemTime::now(),
+                Duration::from_millis(10),
+                Some("test_error"),
+            )
+            .await;
+        assert_eq!(test_telemetry.spans().await.len(), 1);
+        test_telemetry.flush().await.unwrap();
+        assert!(test_telemetry.spans().await.is_empty());
+        test_telemetry.flush().await.unwrap();
+        mock.assert_async().await;
+
+        let mut test_unsampled_context = test_context.clone();
+        test_unsampled_context.trace_header = TraceHeader::parse(
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=0",
+        )
+        .await;
+        test_telemetry
+            .record(
+                &test_unsampled_context,
+                SystemTime::now(),
+                Duration::from_millis(10),
+                None,
+            )
+            .await;
+        assert!(test_telemetry.spans().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn flush_rejected() {
+        let mut test_server = mockito::Server::new_async().await;
+        let test_endpoint = format!("http://{}", test_server.host_with_port());
+        let test_telemetry = Telemetry::create(&test_endpoint).await.unwrap();
+
+        let test_trace_header = TraceHeader::parse(
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1",
+        )
+        .await;
+        let mut test_context = test_context().await;
+        test_context.trace_header = test_trace_header;
+
+        let mock = test_server
+            .mock("POST", "/v1/traces")
+            .with_status(500)
+            .with_body("test_collector_unavailable")
+            .expect(1)
+            .create_async()
+            .await;
+
+        test_telemetry
+            .record(
+                &test_context,
+                SystemTime::now(),
+                Duration::from_millis(10),
+                None,
+            )
+            .await;
+        assert_eq!(
+            test_telemetry.flush().await,
+            Err(TelemetryError::Collector {
+                status: 500,
+                message: String::from("test_collector_unavailable"),
+            }),
+        );
+        mock.assert_async().await;
+    }
+}
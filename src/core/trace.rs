@@ -0,0 +1,133 @@
+use hyper::header::{HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tracing::warn;
+
+pub const X_AMZN_TRACE_ID: &str = "X-Amzn-Trace-Id";
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct TraceHeader {
+    pub root: String,
+    pub parent: Option<String>,
+    pub sampled: Option<bool>,
+    pub lineage: Option<String>,
+}
+
+impl TraceHeader {
+    pub async fn parse(value: &str) -> Option<TraceHeader> {
+        let mut root = None;
+        let mut parent = None;
+        let mut sampled = None;
+        let mut lineage = None;
+
+        for field in value.split(';') {
+            match field.trim().split_once('=') {
+                Some(("Root", value)) => root = Some(value.to_string()),
+                Some(("Parent", value)) => parent = Some(value.to_string()),
+                Some(("Sampled", "1")) => sampled = Some(true),
+                Some(("Sampled", "0")) => sampled = Some(false),
+                Some(("Lineage", value)) => lineage = Some(value.to_string()),
+                _ => continue,
+            }
+        }
+
+        match root {
+            Some(root) => Some(TraceHeader {
+                root,
+                parent,
+                sampled,
+                lineage,
+            }),
+            None => {
+                warn!("| kaon trace | trace header without Root - {}", value);
+                None
+            }
+        }
+    }
+
+    pub async fn propagate(&self, headers: &mut HeaderMap) {
+        match HeaderValue::from_str(&self.to_string()) {
+            Ok(value) => {
+                headers.insert(X_AMZN_TRACE_ID, value);
+            }
+            Err(error) => warn!("| kaon trace | cannot propagate trace header - {}", error),
+        }
+    }
+}
+
+impl fmt::Display for TraceHeader {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Root={}", self.root)?;
+
+        if let Some(parent) = &self.parent {
+            write!(f, ";Parent={}", parent)?;
+        }
+
+        if let Some(sampled) = self.sampled {
+            write!(f, ";Sampled={}", u8::from(sampled))?;
+        }
+
+        if let Some(lineage) = &self.lineage {
+            write!(f, ";Lineage={}", lineage)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn parse() {
+        let test_trace_header = TraceHeader::parse(
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1;Lineage=a87bd80c:0",
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            test_trace_header.root,
+            String::from("1-5759e988-bd862e3fe1be46a994272793"),
+        );
+        assert_eq!(
+            test_trace_header.parent,
+            Some(String::from("53995c3f42cd8ad8")),
+        );
+        assert_eq!(test_trace_header.sampled, Some(true));
+        assert_eq!(test_trace_header.lineage, Some(String::from("a87bd80c:0")));
+        assert_eq!(
+            test_trace_header.to_string(),
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Parent=53995c3f42cd8ad8;Sampled=1;Lineage=a87bd80c:0",
+        );
+
+        let test_partial_trace_header =
+            TraceHeader::parse("Root=1-5759e988-bd862e3fe1be46a994272793;Sampled=?")
+                .await
+                .unwrap();
+        assert_eq!(test_partial_trace_header.parent, None);
+        assert_eq!(test_partial_trace_header.sampled, None);
+        assert_eq!(
+            test_partial_trace_header.to_string(),
+            "Root=1-5759e988-bd862e3fe1be46a994272793",
+        );
+
+        assert!(TraceHeader::parse("Parent=53995c3f42cd8ad8;Sampled=0")
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn propagate() {
+        let test_trace_header =
+            TraceHeader::parse("Root=1-5759e988-bd862e3fe1be46a994272793;Sampled=0")
+                .await
+                .unwrap();
+        let mut test_headers = HeaderMap::new();
+        test_trace_header.propagate(&mut test_headers).await;
+        assert_eq!(
+            test_headers.get(X_AMZN_TRACE_ID).unwrap(),
+            "Root=1-5759e988-bd862e3fe1be46a994272793;Sampled=0",
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use serde_json::Value;
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.path, self.message)
+    }
+}
+
+pub trait Validate {
+    fn validate(&self) -> Result<(), Vec<ValidationError>>;
+}
+
+#[derive(Debug, Default)]
+pub struct Validation {
+    errors: Vec<ValidationError>,
+}
+
+impl Validation {
+    pub fn require(&mut self, valid: bool, path: &str, message: &str) -> &mut Validation {
+        if !valid {
+            self.errors.push(ValidationError {
+                path: path.to_string(),
+                message: message.to_string(),
+            });
+        }
+        self
+    }
+
+    pub fn nested(&mut self, path: &str, nested: &impl Validate) -> &mut Validation {
+        if let Err(errors) = nested.validate() {
+            self.errors
+                .extend(errors.into_iter().map(|error| ValidationError {
+                    path: format!("{}{}", path, error.path),
+                    message: error.message,
+                }));
+        }
+        self
+    }
+
+    pub fn finish(&mut self) -> Result<(), Vec<ValidationError>> {
+        match self.errors.is_empty() {
+            true => Ok(()),
+            false => Err(std::mem::take(&mut self.errors)),
+        }
+    }
+}
+
+pub(crate) fn describe(errors: &[ValidationError]) -> String {
+    errors
+        .iter()
+        .map(ValidationError::to_string)
+        .collect::<Vec<String>>()
+        .join("; ")
+}
+
+macro_rules! always_valid {
+    ($($valid:ty),*) => {
+        $(
+            impl Validate for $valid {
+                fn validate(&self) -> Result<(), Vec<ValidationError>> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+always_valid!((), bool, String, Value, i32, i64, u32, u64, usize, f64);
+
+impl<Item: Validate> Validate for Vec<Item> {
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut validation = Validation::default();
+
+        for (index, item) in self.iter().enumerate() {
+            validation.nested(&format!("[{}]", index), item);
+        }
+
+        validation.finish()
+    }
+}
+
+impl<Item: Validate> Validate for Option<Item> {
+    fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        match self {
+            Some(item) => item.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TestItem {
+        sku: String,
+        quantity: u32,
+    }
+
+    impl Validate for TestItem {
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            Validation::default()
+                .require(!self.sku.is_empty(), ".sku", "must not be empty")
+                .require(self.quantity > 0, ".quantity", "must be positive")
+                .finish()
+        }
+    }
+
+    struct TestOrder {
+        items: Vec<TestItem>,
+    }
+
+    impl Validate for TestOrder {
+        fn validate(&self) -> Result<(), Vec<ValidationError>> {
+            Validation::default()
+                .require(!self.items.is_empty(), "$.items", "must not be empty")
+                .nested("$.items", &self.items)
+                .finish()
+        }
+    }
+
+    #[tokio::test]
+    async fn validate() {
+        let test_order = TestOrder {
+            items: vec![TestItem {
+                sku: String::from("test_sku"),
+                quantity: 1,
+            }],
+        };
+        assert_eq!(test_order.validate(), Ok(()));
+
+        let test_order = TestOrder {
+            items: vec![
+                TestItem {
+                    sku: String::from("test_sku"),
+                    quantity: 1,
+                },
+                TestItem {
+                    sku: String::new(),
+                    quantity: 0,
+                },
+            ],
+        };
+        let test_errors = test_order.validate().unwrap_err();
+        assert_eq!(
+            describe(&test_errors),
+            "$.items[1].sku must not be empty; $.items[1].quantity must be positive",
+        );
+
+        let test_errors = TestOrder { items: Vec::new() }.validate().unwrap_err();
+        assert_eq!(test_errors.len(), 1);
+        assert_eq!(test_errors[0].path, "$.items");
+
+        assert_eq!(String::from("test_response").validate(), Ok(()));
+        assert_eq!(None::<TestOrder>.validate(), Ok(()));
+    }
+}
@@ -1,4 +1,35 @@
 mod core;
 
+#[cfg(feature = "dynamodb")]
+pub use crate::core::aws::dynamodb::DynamoDbClient;
+#[cfg(feature = "s3")]
+pub use crate::core::aws::s3::S3Client;
+#[cfg(feature = "sns")]
+pub use crate::core::aws::sns::SnsClient;
+#[cfg(feature = "sqs")]
+pub use crate::core::aws::sqs::SqsClient;
+#[cfg(feature = "aws")]
+pub use crate::core::aws::{AwsClient, AwsError};
 pub use crate::core::context::Context;
-pub use crate::core::Kaon;
+pub use crate::core::credentials::Credentials;
+pub use crate::core::error::{ErrorRequest, ErrorType};
+pub use crate::core::history::{History, InvocationRecord, Outcome};
+pub use crate::core::hooks::{HookFuture, Hooks};
+pub use crate::core::idempotency::{
+    Acquired, FileStore, Idempotency, IdempotencyKey, IdempotencyRecord, IdempotencyStatus,
+    IdempotencyStore, InMemoryStore, StoreFuture,
+};
+pub use crate::core::initialization::{Initialization, InitializationType};
+pub use crate::core::invocation::Invocation;
+pub use crate::core::metrics::{MetricUnit, Metrics};
+pub use crate::core::parameters::{Parameters, ParametersError};
+pub use crate::core::payload::{DeserializationError, Payload};
+pub use crate::core::redaction::{Redaction, REDACTED};
+pub use crate::core::registry::{DecayFuture, HandlerRegistry};
+pub use crate::core::router::{EventRouter, EventSource};
+pub use crate::core::sigv4::{Signer, SigningError};
+#[cfg(feature = "telemetry")]
+pub use crate::core::telemetry::{Telemetry, TelemetryError};
+pub use crate::core::trace::{TraceHeader, X_AMZN_TRACE_ID};
+pub use crate::core::validation::{Validate, Validation, ValidationError};
+pub use crate::core::{ApiConfig, HandlerError, Kaon, Panicked};